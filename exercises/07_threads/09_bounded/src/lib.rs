@@ -2,6 +2,7 @@
 use crate::data::{Ticket, TicketDraft};
 use crate::store::{TicketId, TicketStore};
 use std::sync::mpsc::{sync_channel, Receiver, Sender, SyncSender};
+use std::thread::JoinHandle;
 
 pub mod data;
 pub mod store;
@@ -12,49 +13,118 @@ pub struct TicketStoreClient {
 }
 
 impl TicketStoreClient {
-    pub fn insert(&self, draft: TicketDraft) -> Result<TicketId, String> {
+    pub fn insert(&self, draft: TicketDraft) -> Result<TicketId, ClientError> {
         let (response_sender, response_receiver) = std::sync::mpsc::channel();
 
-        self.sender.send(Command::Insert {
-            draft,
-            response_channel: response_sender,
-        }).map_err(|e| e.to_string())?;
-        
-        // Receive the response - we can unwrap here because the server
-        // will always send a response before closing the channel
-        Ok(response_receiver.recv().unwrap())
+        self.sender
+            .send(Command::Insert {
+                draft,
+                response_channel: response_sender,
+            })
+            .map_err(|_| ServerGone)?;
+
+        // The server drops the response channel without replying
+        // if it stops before getting to our command.
+        response_receiver.recv().map_err(|_| ServerGone)?
     }
 
-    pub fn get(&self, id: TicketId) -> Result<Option<Ticket>, String> {
+    pub fn get(&self, id: TicketId) -> Result<Ticket, ClientError> {
         let (response_sender, response_receiver) = std::sync::mpsc::channel();
 
-        self.sender.send(Command::Get {
-            id,
-            response_channel: response_sender,
-        }).map_err(|e| e.to_string())?;
-        
+        self.sender
+            .send(Command::Get {
+                id,
+                response_channel: response_sender,
+            })
+            .map_err(|_| ServerGone)?;
+
         // Receive the response
-        Ok(response_receiver.recv().unwrap())
+        response_receiver.recv().map_err(|_| ServerGone)?
+    }
+}
+
+/// Everything that can go wrong when talking to the server.
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    /// See [`ServerGone`].
+    #[error("The server is no longer running")]
+    Disconnected,
+    #[error("There is no ticket with id {0:?}")]
+    NotFound(TicketId),
+}
+
+/// The server has stopped, either because it was shut down or because it panicked.
+/// Clients report it as [`ClientError::Disconnected`].
+#[derive(Debug, thiserror::Error)]
+#[error("The server is no longer running")]
+pub struct ServerGone;
+
+impl From<ServerGone> for ClientError {
+    fn from(_: ServerGone) -> Self {
+        ClientError::Disconnected
+    }
+}
+
+/// The server thread panicked. The panic message is included, if there was one.
+#[derive(Debug, thiserror::Error)]
+#[error("The server panicked: {0}")]
+pub struct ServerPanicked(pub String);
+
+/// Controls the lifecycle of a server started with [`launch`].
+pub struct ServerHandle {
+    sender: SyncSender<Command>,
+    thread: JoinHandle<()>,
+}
+
+impl ServerHandle {
+    /// Stop the server once it has processed the commands that are already queued,
+    /// then wait for it to exit.
+    /// Commands sent after the shutdown request fail with [`ServerGone`].
+    pub fn shutdown(self) -> Result<(), ServerPanicked> {
+        // If the server is already gone, `join` will tell us why.
+        let _ = self.sender.send(Command::Shutdown);
+        self.join()
+    }
+
+    /// Wait for the server to exit, which happens when every client has been dropped.
+    pub fn join(self) -> Result<(), ServerPanicked> {
+        let ServerHandle { sender, thread } = self;
+        drop(sender);
+        thread.join().map_err(|payload| {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            ServerPanicked(message)
+        })
     }
 }
 
-pub fn launch(capacity: usize) -> TicketStoreClient {
+pub fn launch(capacity: usize) -> (TicketStoreClient, ServerHandle) {
     let (sender, receiver) = sync_channel(capacity);
-    std::thread::spawn(move || server(receiver));
-    TicketStoreClient { sender }
+    let thread = std::thread::spawn(move || server(receiver));
+    let handle = ServerHandle {
+        sender: sender.clone(),
+        thread,
+    };
+    (TicketStoreClient { sender }, handle)
 }
 
 enum Command {
     Insert {
         draft: TicketDraft,
-        response_channel: Sender<TicketId>,
+        response_channel: Sender<Result<TicketId, ClientError>>,
     },
     Get {
         id: TicketId,
-        response_channel: Sender<Option<Ticket>>,
+        response_channel: Sender<Result<Ticket, ClientError>>,
     },
+    Shutdown,
 }
 
+// Public as it's always been, even though only `launch` can create the commands it takes.
+#[allow(private_interfaces)]
 pub fn server(receiver: Receiver<Command>) {
     let mut store = TicketStore::new();
     loop {
@@ -66,19 +136,19 @@ pub fn server(receiver: Receiver<Command>) {
                 let id = store.add_ticket(draft);
                 // Send the response back to the client
                 // Ignore the error if the client has disconnected
-                let _ = response_channel.send(id);
+                let _ = response_channel.send(Ok(id));
             }
             Ok(Command::Get {
                 id,
                 response_channel,
             }) => {
-                let ticket = store.get(id).cloned();
+                let ticket = store.get(id).cloned().ok_or(ClientError::NotFound(id));
                 // Send the response back to the client
                 let _ = response_channel.send(ticket);
             }
-            Err(_) => {
-                // There are no more senders, so we can safely break
-                // and shut down the server.
+            Ok(Command::Shutdown) | Err(_) => {
+                // We've been asked to stop, or there are no more senders:
+                // either way, we can safely break and shut down the server.
                 break;
             }
         }
@@ -4,7 +4,7 @@ use ticket_fields::test_helpers::{ticket_description, ticket_title};
 
 #[test]
 fn works() {
-    let client = launch(5);
+    let (client, _server) = launch(5);
     let draft = TicketDraft {
         title: ticket_title(),
         description: ticket_description(),
@@ -12,9 +12,30 @@ fn works() {
     let ticket_id = client.insert(draft.clone()).unwrap();
 
     let client2 = client.clone();
-    let ticket = client2.get(ticket_id).unwrap().unwrap();
+    let ticket = client2.get(ticket_id).unwrap();
     assert_eq!(ticket_id, ticket.id);
     assert_eq!(ticket.status, Status::ToDo);
     assert_eq!(ticket.title, draft.title);
     assert_eq!(ticket.description, draft.description);
 }
+
+#[test]
+fn shutdown() {
+    let (client, server) = launch(5);
+    let draft = TicketDraft {
+        title: ticket_title(),
+        description: ticket_description(),
+    };
+    let ticket_id = client.insert(draft).unwrap();
+
+    server.shutdown().unwrap();
+
+    assert!(client.get(ticket_id).is_err());
+}
+
+#[test]
+fn join_returns_once_every_client_is_gone() {
+    let (client, server) = launch(5);
+    drop(client);
+    server.join().unwrap();
+}
@@ -7,6 +7,8 @@ pub struct Ticket {
     pub title: TicketTitle,
     pub description: TicketDescription,
     pub status: Status,
+    /// Bumped every time the ticket is modified.
+    pub version: u64,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -18,11 +20,32 @@ pub struct TicketDraft {
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TicketPatch {
     pub id: TicketId,
+    /// The version of the ticket the patch was built against.
+    /// The update is rejected if the ticket has been modified since.
+    pub expected_version: u64,
     pub title: Option<TicketTitle>,
     pub description: Option<TicketDescription>,
     pub status: Option<Status>,
 }
 
+/// A single step of a batch, see [`TicketStoreClient::batch`](crate::TicketStoreClient::batch).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Operation {
+    Insert(TicketDraft),
+    Get(TicketId),
+    Update(TicketPatch),
+    Remove(TicketId),
+}
+
+/// The result of a successful [`Operation`], in the same position as the operation itself.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OperationOutcome {
+    Inserted(TicketId),
+    Got(Ticket),
+    Updated,
+    Removed(Ticket),
+}
+
 #[derive(Clone, Debug, Copy, PartialEq, Eq)]
 pub enum Status {
     ToDo,
@@ -0,0 +1,107 @@
+//! Change notifications for subscribers of the ticket store.
+
+use std::sync::mpsc::{SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use crate::data::{Status, Ticket};
+use crate::store::TicketId;
+
+/// How many events can pile up for a subscriber, unless specified otherwise.
+pub const DEFAULT_SUBSCRIBER_BUFFER: usize = 128;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TicketEvent {
+    Created(Ticket),
+    Updated { before: Ticket, after: Ticket },
+    Removed(Ticket),
+}
+
+impl TicketEvent {
+    /// The ticket(s) affected by the event: both states for an update, one otherwise.
+    fn tickets(&self) -> impl Iterator<Item = &Ticket> {
+        let (first, second) = match self {
+            TicketEvent::Created(ticket) | TicketEvent::Removed(ticket) => (ticket, None),
+            TicketEvent::Updated { before, after } => (before, Some(after)),
+        };
+        std::iter::once(first).chain(second)
+    }
+}
+
+/// Selects the events a subscriber is interested in.
+///
+/// An update matches if either the old or the new state of the ticket matches.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TicketFilter {
+    pub id: Option<TicketId>,
+    pub status: Option<Status>,
+}
+
+impl TicketFilter {
+    /// A filter that lets every event through.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    pub fn matches(&self, event: &TicketEvent) -> bool {
+        event.tickets().any(|ticket| {
+            self.id.is_none_or(|id| ticket.id == id)
+                && self.status.is_none_or(|status| ticket.status == status)
+        })
+    }
+}
+
+/// What the server does when a subscriber's buffer is full.
+///
+/// The server never waits for a subscriber to catch up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OnFull {
+    /// Skip the event, the subscriber will miss it.
+    DropEvent,
+    /// Drop the subscription: the subscriber will see its receiver disconnect
+    /// once it has drained the buffered events.
+    Disconnect,
+}
+
+pub(crate) struct Subscriber {
+    pub(crate) filter: TicketFilter,
+    pub(crate) on_full: OnFull,
+    /// Shared by every shard the subscription was sent to: whichever drops the
+    /// subscriber takes the sender, so that the receiver disconnects right away
+    /// and the other shards drop the subscriber too.
+    pub(crate) sender: Arc<Mutex<Option<SyncSender<TicketEvent>>>>,
+}
+
+#[derive(Default)]
+pub(crate) struct Subscribers(Vec<Subscriber>);
+
+impl Subscribers {
+    pub(crate) fn add(&mut self, subscriber: Subscriber) {
+        self.0.push(subscriber);
+    }
+
+    /// Deliver `event` to every interested subscriber, without blocking.
+    pub(crate) fn publish(&mut self, event: TicketEvent) {
+        self.0.retain(|subscriber| {
+            let mut sender = subscriber
+                .sender
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let Some(active) = sender.as_ref() else {
+                // Another shard dropped the subscriber.
+                return false;
+            };
+            if !subscriber.filter.matches(&event) {
+                return true;
+            }
+            let keep = match active.try_send(event.clone()) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => subscriber.on_full == OnFull::DropEvent,
+                Err(TrySendError::Disconnected(_)) => false,
+            };
+            if !keep {
+                *sender = None;
+            }
+            keep
+        });
+    }
+}
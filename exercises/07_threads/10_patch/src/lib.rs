@@ -1,115 +1,654 @@
-use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::ops::{Bound, RangeBounds};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, RecvTimeoutError, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
 
-// TODO: Implement the patching functionality.
-use crate::data::{Ticket, TicketDraft, TicketPatch};
+use crate::data::{Operation, OperationOutcome, Ticket, TicketDraft, TicketPatch};
+use crate::events::{
+    OnFull, Subscriber, Subscribers, TicketEvent, TicketFilter, DEFAULT_SUBSCRIBER_BUFFER,
+};
+use crate::overflow::{OverflowCounters, OverflowMetrics, OverflowPolicy};
 use crate::store::{TicketId, TicketStore};
 
 pub mod data;
+pub mod events;
+pub mod overflow;
 pub mod store;
 
 #[derive(Clone)]
 pub struct TicketStoreClient {
-    sender: SyncSender<Command>,
+    /// One queue per shard, see [`launch_sharded`].
+    shards: Vec<SyncSender<Envelope>>,
+    /// The shard that will receive the next insertion.
+    next_shard: Arc<AtomicUsize>,
+    timeout: Option<Duration>,
+    overflow_policy: OverflowPolicy,
+    overflow_counters: Arc<OverflowCounters>,
 }
 
 impl TicketStoreClient {
-    pub fn insert(&self, draft: TicketDraft) -> Result<TicketId, OverloadedError> {
+    /// Give up on requests that take longer than `timeout`, unless they
+    /// specify their own deadline.
+    /// Without a default timeout, the client waits for as long as it takes.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// How many requests, across all the clones of this client, took each path
+    /// of its [`OverflowPolicy`].
+    pub fn overflow_metrics(&self) -> OverflowMetrics {
+        self.overflow_counters.snapshot()
+    }
+
+    fn default_deadline(&self) -> Option<Instant> {
+        self.timeout.map(|timeout| Instant::now() + timeout)
+    }
+
+    /// The shard that owns the ticket with the given `id`.
+    fn shard_of(&self, id: TicketId) -> usize {
+        id.shard(self.shards.len())
+    }
+
+    /// Spread insertions evenly across shards.
+    fn shard_for_insertion(&self) -> usize {
+        self.next_shard.fetch_add(1, Ordering::Relaxed) % self.shards.len()
+    }
+
+    fn send(
+        &self,
+        shard: usize,
+        command: Command,
+        deadline: Option<Instant>,
+    ) -> Result<(), ClientError> {
+        overflow::send(
+            &self.shards[shard],
+            Envelope { command, deadline },
+            self.overflow_policy,
+            &self.overflow_counters,
+        )
+    }
+
+    /// Send the command built by `command` to `shard` and wait for the server to reply,
+    /// up to `deadline` if there is one.
+    fn call<T, E>(
+        &self,
+        shard: usize,
+        deadline: Option<Instant>,
+        command: impl FnOnce(SyncSender<Result<T, E>>) -> Command,
+    ) -> Result<T, E>
+    where
+        E: From<ClientError>,
+    {
+        let response_receiver = self.submit(shard, deadline, command)?;
+        Self::wait(response_receiver, deadline)
+    }
+
+    /// Send the command built by `command` to `shard`, without waiting for a reply.
+    fn submit<T, E>(
+        &self,
+        shard: usize,
+        deadline: Option<Instant>,
+        command: impl FnOnce(SyncSender<Result<T, E>>) -> Command,
+    ) -> Result<Receiver<Result<T, E>>, ClientError> {
         let (response_sender, response_receiver) = sync_channel(1);
-        self.sender
-            .try_send(Command::Insert {
+        self.send(shard, command(response_sender), deadline)?;
+        Ok(response_receiver)
+    }
+
+    /// Wait for the reply to a submitted command, up to `deadline` if there is one.
+    fn wait<T, E>(
+        response_receiver: Receiver<Result<T, E>>,
+        deadline: Option<Instant>,
+    ) -> Result<T, E>
+    where
+        E: From<ClientError>,
+    {
+        match deadline {
+            None => response_receiver
+                .recv()
+                .map_err(|_| ClientError::from(ServerGone))?,
+            Some(deadline) => response_receiver
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                .map_err(|e| match e {
+                    RecvTimeoutError::Timeout => ClientError::Timeout,
+                    RecvTimeoutError::Disconnected => ServerGone.into(),
+                })?,
+        }
+    }
+
+    pub fn insert(&self, draft: TicketDraft) -> Result<TicketId, ClientError> {
+        self.insert_by(draft, self.default_deadline())
+    }
+
+    /// Insert a ticket, giving up with [`ClientError::Timeout`] if the server
+    /// hasn't processed the request by `deadline`.
+    pub fn insert_with_deadline(
+        &self,
+        draft: TicketDraft,
+        deadline: Instant,
+    ) -> Result<TicketId, ClientError> {
+        self.insert_by(draft, Some(deadline))
+    }
+
+    /// Insert a ticket, giving up with [`ClientError::Timeout`] after `timeout`.
+    pub fn insert_timeout(
+        &self,
+        draft: TicketDraft,
+        timeout: Duration,
+    ) -> Result<TicketId, ClientError> {
+        self.insert_by(draft, Some(Instant::now() + timeout))
+    }
+
+    fn insert_by(
+        &self,
+        draft: TicketDraft,
+        deadline: Option<Instant>,
+    ) -> Result<TicketId, ClientError> {
+        self.call(self.shard_for_insertion(), deadline, |response_channel| {
+            Command::Insert {
                 draft,
-                response_channel: response_sender,
-            })
-            .map_err(|_| OverloadedError)?;
-        Ok(response_receiver.recv().unwrap())
+                response_channel,
+            }
+        })
     }
 
-    pub fn get(&self, id: TicketId) -> Result<Option<Ticket>, OverloadedError> {
-        let (response_sender, response_receiver) = sync_channel(1);
-        self.sender
-            .try_send(Command::Get {
+    pub fn get(&self, id: TicketId) -> Result<Ticket, ClientError> {
+        self.get_by(id, self.default_deadline())
+    }
+
+    /// Get a ticket, giving up with [`ClientError::Timeout`] if the server
+    /// hasn't processed the request by `deadline`.
+    pub fn get_with_deadline(
+        &self,
+        id: TicketId,
+        deadline: Instant,
+    ) -> Result<Ticket, ClientError> {
+        self.get_by(id, Some(deadline))
+    }
+
+    /// Get a ticket, giving up with [`ClientError::Timeout`] after `timeout`.
+    pub fn get_timeout(&self, id: TicketId, timeout: Duration) -> Result<Ticket, ClientError> {
+        self.get_by(id, Some(Instant::now() + timeout))
+    }
+
+    fn get_by(&self, id: TicketId, deadline: Option<Instant>) -> Result<Ticket, ClientError> {
+        self.call(self.shard_of(id), deadline, |response_channel| {
+            Command::Get {
                 id,
-                response_channel: response_sender,
-            })
-            .map_err(|_| OverloadedError)?;
-        Ok(response_receiver.recv().unwrap())
+                response_channel,
+            }
+        })
     }
 
-    pub fn update(&self, patch: TicketPatch) -> Result<(), OverloadedError> {
-        let (response_sender, response_receiver) = sync_channel(1);
-        self.sender
-            .try_send(Command::Update {
+    pub fn update(&self, patch: TicketPatch) -> Result<(), ClientError> {
+        self.update_by(patch, self.default_deadline())
+    }
+
+    /// Update a ticket, giving up with [`ClientError::Timeout`] if the server
+    /// hasn't processed the request by `deadline`.
+    pub fn update_with_deadline(
+        &self,
+        patch: TicketPatch,
+        deadline: Instant,
+    ) -> Result<(), ClientError> {
+        self.update_by(patch, Some(deadline))
+    }
+
+    /// Update a ticket, giving up with [`ClientError::Timeout`] after `timeout`.
+    pub fn update_timeout(&self, patch: TicketPatch, timeout: Duration) -> Result<(), ClientError> {
+        self.update_by(patch, Some(Instant::now() + timeout))
+    }
+
+    fn update_by(&self, patch: TicketPatch, deadline: Option<Instant>) -> Result<(), ClientError> {
+        self.call(self.shard_of(patch.id), deadline, |response_channel| {
+            Command::Update {
                 patch,
-                response_channel: response_sender,
+                response_channel,
+            }
+        })
+    }
+
+    /// Remove a ticket, returning it.
+    pub fn remove(&self, id: TicketId) -> Result<Ticket, ClientError> {
+        self.call(
+            self.shard_of(id),
+            self.default_deadline(),
+            |response_channel| Command::Remove {
+                id,
+                response_channel,
+            },
+        )
+    }
+
+    /// Get all the tickets whose id falls within `range`, in id order.
+    pub fn range(&self, range: impl RangeBounds<TicketId>) -> Result<Vec<Ticket>, ClientError> {
+        let range = (range.start_bound().cloned(), range.end_bound().cloned());
+        let deadline = self.default_deadline();
+        // Ask every shard first, so that they can work concurrently.
+        let replies = (0..self.shards.len())
+            .map(|shard| {
+                self.submit(shard, deadline, |response_channel| Command::Range {
+                    range,
+                    response_channel,
+                })
             })
-            .map_err(|_| OverloadedError)?;
-        Ok(response_receiver.recv().expect("server dropped"))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut tickets = Vec::new();
+        for reply in replies {
+            tickets.extend(Self::wait(reply, deadline)?);
+        }
+        // Each shard's tickets are already sorted: the sort boils down to merging them.
+        tickets.sort_by_key(|ticket| ticket.id);
+        Ok(tickets)
+    }
+
+    /// Get notified of every change matching `filter`.
+    ///
+    /// Up to [`DEFAULT_SUBSCRIBER_BUFFER`] events are buffered: a subscriber that
+    /// falls further behind is disconnected, see [`TicketStoreClient::subscribe_with`].
+    pub fn subscribe(&self, filter: TicketFilter) -> Result<Receiver<TicketEvent>, ClientError> {
+        self.subscribe_with(filter, DEFAULT_SUBSCRIBER_BUFFER, OnFull::Disconnect)
+    }
+
+    /// Get notified of every change matching `filter`, buffering up to `buffer` events.
+    /// `on_full` decides what happens to the subscription when the buffer is full.
+    ///
+    /// With a sharded server, the buffer is shared by all shards: events from the same
+    /// shard are delivered in order, events from different shards may be interleaved.
+    /// A subscriber disconnected by one shard is disconnected from all of them.
+    pub fn subscribe_with(
+        &self,
+        filter: TicketFilter,
+        buffer: usize,
+        on_full: OnFull,
+    ) -> Result<Receiver<TicketEvent>, ClientError> {
+        let (sender, receiver) = sync_channel(buffer);
+        let sender = Arc::new(Mutex::new(Some(sender)));
+        for shard in 0..self.shards.len() {
+            let subscriber = Subscriber {
+                filter: filter.clone(),
+                on_full,
+                sender: sender.clone(),
+            };
+            self.send(shard, Command::Subscribe(subscriber), None)?;
+        }
+        Ok(receiver)
+    }
+
+    /// Submit all `operations` in a single round trip.
+    /// They are applied atomically: if any of them fails, none of them is.
+    ///
+    /// With a sharded server, all the existing tickets targeted by the batch must
+    /// live on the same shard, which is also where new tickets are inserted.
+    pub fn batch(
+        &self,
+        operations: Vec<Operation>,
+    ) -> Result<Vec<OperationOutcome>, TransactionError> {
+        let mut shards = operations.iter().filter_map(|operation| match operation {
+            Operation::Insert(_) => None,
+            Operation::Get(id) | Operation::Remove(id) => Some(self.shard_of(*id)),
+            Operation::Update(patch) => Some(self.shard_of(patch.id)),
+        });
+        let shard = match shards.next() {
+            Some(shard) if shards.all(|other| other == shard) => shard,
+            Some(_) => return Err(ClientError::from(ValidationError::CrossShard).into()),
+            None => self.shard_for_insertion(),
+        };
+        self.call(shard, self.default_deadline(), |response_channel| {
+            Command::Batch {
+                operations,
+                response_channel,
+            }
+        })
     }
+
+    /// Build a batch with `build` and submit it, see [`TicketStoreClient::batch`].
+    pub fn transaction<F>(&self, build: F) -> Result<Vec<OperationOutcome>, TransactionError>
+    where
+        F: FnOnce(&mut Transaction),
+    {
+        let mut transaction = Transaction::default();
+        build(&mut transaction);
+        self.batch(transaction.operations)
+    }
+}
+
+/// Collects the operations of a transaction, see [`TicketStoreClient::transaction`].
+#[derive(Default)]
+pub struct Transaction {
+    operations: Vec<Operation>,
 }
 
+impl Transaction {
+    pub fn insert(&mut self, draft: TicketDraft) -> &mut Self {
+        self.operations.push(Operation::Insert(draft));
+        self
+    }
+
+    pub fn get(&mut self, id: TicketId) -> &mut Self {
+        self.operations.push(Operation::Get(id));
+        self
+    }
+
+    pub fn update(&mut self, patch: TicketPatch) -> &mut Self {
+        self.operations.push(Operation::Update(patch));
+        self
+    }
+
+    pub fn remove(&mut self, id: TicketId) -> &mut Self {
+        self.operations.push(Operation::Remove(id));
+        self
+    }
+}
+
+/// Everything that can go wrong when talking to the server.
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("The store is overloaded")]
+    Overloaded,
+    /// See [`ServerGone`].
+    #[error("The server is no longer running")]
+    Disconnected,
+    /// The request wasn't processed before its deadline.
+    /// The server skips requests that have already expired.
+    #[error("The server did not reply in time")]
+    Timeout,
+    #[error("There is no ticket with id {0:?}")]
+    NotFound(TicketId),
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+}
+
+/// The server refused to apply a command.
+#[derive(Debug, thiserror::Error)]
+pub enum ValidationError {
+    /// The ticket has been modified since the patch was built.
+    /// `current` holds the latest version, so the caller can merge and retry.
+    #[error("The ticket has been modified concurrently (current version: {})", current.version)]
+    Conflict { current: Ticket },
+    /// A batch targeted tickets owned by different shards.
+    #[error("A transaction cannot span multiple shards")]
+    CrossShard,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TransactionError {
+    #[error(transparent)]
+    Client(#[from] ClientError),
+    /// The operation at `index` failed, so the whole batch was rolled back.
+    #[error("Operation #{index} failed, the transaction has been rolled back")]
+    Aborted {
+        index: usize,
+        #[source]
+        source: ClientError,
+    },
+}
+
+/// The server has stopped, either because it was shut down or because it panicked.
+/// Clients report it as [`ClientError::Disconnected`].
 #[derive(Debug, thiserror::Error)]
-#[error("The store is overloaded")]
-pub struct OverloadedError;
+#[error("The server is no longer running")]
+pub struct ServerGone;
+
+impl From<ServerGone> for ClientError {
+    fn from(_: ServerGone) -> Self {
+        ClientError::Disconnected
+    }
+}
+
+/// The server thread panicked. The panic message is included, if there was one.
+#[derive(Debug, thiserror::Error)]
+#[error("The server panicked: {0}")]
+pub struct ServerPanicked(pub String);
+
+/// Controls the lifecycle of a server started with [`launch`].
+pub struct ServerHandle {
+    senders: Vec<SyncSender<Envelope>>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl ServerHandle {
+    /// Stop the server once it has processed the commands that are already queued,
+    /// then wait for it to exit. All shards are stopped.
+    /// Commands sent after the shutdown request fail with [`ServerGone`].
+    pub fn shutdown(self) -> Result<(), ServerPanicked> {
+        for sender in &self.senders {
+            // If the server is already gone, `join` will tell us why.
+            let _ = sender.send(Envelope {
+                command: Command::Shutdown,
+                deadline: None,
+            });
+        }
+        self.join()
+    }
+
+    /// Wait for the server to exit, which happens when every client has been dropped.
+    /// If several shards panicked, the first panic is reported.
+    pub fn join(self) -> Result<(), ServerPanicked> {
+        let ServerHandle { senders, threads } = self;
+        drop(senders);
+        let mut outcome = Ok(());
+        for thread in threads {
+            if let Err(payload) = thread.join() {
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_default();
+                outcome = outcome.and(Err(ServerPanicked(message)));
+            }
+        }
+        outcome
+    }
+}
+
+/// Launch a server that accepts up to `capacity` pending requests.
+/// Requests sent while the queue is full fail with [`ClientError::Overloaded`].
+pub fn launch(capacity: usize) -> (TicketStoreClient, ServerHandle) {
+    launch_with_policy(capacity, OverflowPolicy::FailFast)
+}
 
-pub fn launch(capacity: usize) -> TicketStoreClient {
-    let (sender, receiver) = sync_channel(capacity);
-    std::thread::spawn(move || server(receiver));
-    TicketStoreClient { sender }
+/// Launch a server that accepts up to `capacity` pending requests.
+/// Clients handle requests sent while the queue is full according to `overflow_policy`.
+pub fn launch_with_policy(
+    capacity: usize,
+    overflow_policy: OverflowPolicy,
+) -> (TicketStoreClient, ServerHandle) {
+    start(1, capacity, overflow_policy)
+}
+
+/// Launch `shards` server threads, each owning a share of the tickets
+/// and accepting up to `capacity` pending requests.
+///
+/// Requests about a ticket are routed to the shard that owns it, while new tickets
+/// are spread evenly across shards. Ticket ids are interleaved: shard `i`
+/// hands out `i`, `i + shards`, `i + 2 * shards`, and so on.
+///
+/// # Panics
+///
+/// Panics if `shards` is zero.
+pub fn launch_sharded(shards: usize, capacity: usize) -> (TicketStoreClient, ServerHandle) {
+    start(shards, capacity, OverflowPolicy::FailFast)
+}
+
+fn start(
+    shards: usize,
+    capacity: usize,
+    overflow_policy: OverflowPolicy,
+) -> (TicketStoreClient, ServerHandle) {
+    assert!(shards > 0, "A server needs at least one shard");
+    let (senders, threads): (Vec<_>, Vec<_>) = (0..shards)
+        .map(|shard| {
+            let (sender, receiver) = sync_channel(capacity);
+            let store = TicketStore::sharded(shard, shards);
+            let thread = std::thread::spawn(move || server(receiver, store));
+            (sender, thread)
+        })
+        .unzip();
+    let handle = ServerHandle {
+        senders: senders.clone(),
+        threads,
+    };
+    let client = TicketStoreClient {
+        shards: senders,
+        next_shard: Arc::default(),
+        timeout: None,
+        overflow_policy,
+        overflow_counters: Arc::default(),
+    };
+    (client, handle)
+}
+
+/// A command, alongside the instant after which the client is no longer waiting for it.
+struct Envelope {
+    command: Command,
+    deadline: Option<Instant>,
 }
 
 enum Command {
     Insert {
         draft: TicketDraft,
-        response_channel: SyncSender<TicketId>,
+        response_channel: SyncSender<Result<TicketId, ClientError>>,
     },
     Get {
         id: TicketId,
-        response_channel: SyncSender<Option<Ticket>>,
+        response_channel: SyncSender<Result<Ticket, ClientError>>,
     },
     Update {
         patch: TicketPatch,
-        response_channel: SyncSender<()>,
+        response_channel: SyncSender<Result<(), ClientError>>,
+    },
+    Remove {
+        id: TicketId,
+        response_channel: SyncSender<Result<Ticket, ClientError>>,
+    },
+    Batch {
+        operations: Vec<Operation>,
+        response_channel: SyncSender<Result<Vec<OperationOutcome>, TransactionError>>,
     },
+    Range {
+        range: (Bound<TicketId>, Bound<TicketId>),
+        response_channel: SyncSender<Result<Vec<Ticket>, ClientError>>,
+    },
+    Subscribe(Subscriber),
+    Shutdown,
 }
 
-pub fn server(receiver: Receiver<Command>) {
-    let mut store = TicketStore::new();
+impl Command {
+    /// Let the client know that its request expired before the server got to it.
+    fn reject_expired(self) {
+        match self {
+            Command::Insert {
+                response_channel, ..
+            } => {
+                let _ = response_channel.send(Err(ClientError::Timeout));
+            }
+            Command::Get {
+                response_channel, ..
+            }
+            | Command::Remove {
+                response_channel, ..
+            } => {
+                let _ = response_channel.send(Err(ClientError::Timeout));
+            }
+            Command::Update {
+                response_channel, ..
+            } => {
+                let _ = response_channel.send(Err(ClientError::Timeout));
+            }
+            Command::Batch {
+                response_channel, ..
+            } => {
+                let _ = response_channel.send(Err(ClientError::Timeout.into()));
+            }
+            Command::Range {
+                response_channel, ..
+            } => {
+                let _ = response_channel.send(Err(ClientError::Timeout));
+            }
+            Command::Subscribe(_) | Command::Shutdown => {}
+        }
+    }
+}
+
+/// The loop run by each server thread, see [`launch`].
+// Public as it's always been, even though only `launch` can create the commands it takes.
+#[allow(private_interfaces)]
+pub fn server(receiver: Receiver<Envelope>, mut store: TicketStore) {
+    let mut subscribers = Subscribers::default();
     loop {
-        match receiver.recv() {
+        let command = match receiver.recv() {
+            Ok(Envelope {
+                command,
+                deadline: Some(deadline),
+            }) if deadline <= Instant::now() => {
+                // Nobody is waiting for the outcome anymore: don't bother.
+                command.reject_expired();
+                continue;
+            }
+            envelope => envelope.map(|envelope| envelope.command),
+        };
+        match command {
             Ok(Command::Insert {
                 draft,
                 response_channel,
             }) => {
                 let id = store.add_ticket(draft);
-                let _ = response_channel.send(id);
+                subscribers.publish(TicketEvent::Created(store.get(id).unwrap().clone()));
+                let _ = response_channel.send(Ok(id));
             }
             Ok(Command::Get {
                 id,
                 response_channel,
             }) => {
-                let ticket = store.get(id);
-                let _ = response_channel.send(ticket.cloned());
+                let ticket = store.get(id).cloned().ok_or(ClientError::NotFound(id));
+                let _ = response_channel.send(ticket);
             }
             Ok(Command::Update {
                 patch,
                 response_channel,
             }) => {
-                if let Some(ticket) = store.get_mut(patch.id) {
-                    if let Some(t) = patch.title {
-                        ticket.title = t;
-                    }
-                    if let Some(d) = patch.description {
-                        ticket.description = d;
-                    }
-                    if let Some(s) = patch.status {
-                        ticket.status = s;
-                    }
+                let outcome = store.update(patch).map(|event| subscribers.publish(event));
+                let _ = response_channel.send(outcome);
+            }
+            Ok(Command::Remove {
+                id,
+                response_channel,
+            }) => {
+                let removed = store.remove(id).ok_or(ClientError::NotFound(id));
+                if let Ok(ticket) = &removed {
+                    subscribers.publish(TicketEvent::Removed(ticket.clone()));
                 }
-                let _ = response_channel.send(());
+                let _ = response_channel.send(removed);
+            }
+            Ok(Command::Batch {
+                operations,
+                response_channel,
+            }) => {
+                let outcome = match store.apply_batch(operations) {
+                    Ok((outcomes, events)) => {
+                        for event in events {
+                            subscribers.publish(event);
+                        }
+                        Ok(outcomes)
+                    }
+                    Err((index, source)) => Err(TransactionError::Aborted { index, source }),
+                };
+                let _ = response_channel.send(outcome);
+            }
+            Ok(Command::Range {
+                range,
+                response_channel,
+            }) => {
+                let _ = response_channel.send(Ok(store.range(range)));
             }
-            Err(_) => {
-                // There are no more senders, so we can safely break
-                // and shut down the server.
+            Ok(Command::Subscribe(subscriber)) => subscribers.add(subscriber),
+            Ok(Command::Shutdown) | Err(_) => {
+                // We've been asked to stop, or there are no more senders:
+                // either way, we can safely break and shut down the server.
                 break;
             }
         }
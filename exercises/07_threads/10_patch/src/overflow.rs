@@ -0,0 +1,147 @@
+//! What the client does when the server's queue is full.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{SyncSender, TrySendError};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crate::{ClientError, ServerGone};
+
+/// How long to wait before checking again whether there's room in the queue,
+/// when blocking with a timeout.
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+/// The delay before the first retry, doubled at every subsequent attempt.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(1);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Fail immediately with [`ClientError::Overloaded`].
+    #[default]
+    FailFast,
+    /// Wait for as long as it takes for the server to make room.
+    Block,
+    /// Wait for the server to make room, failing with [`ClientError::Overloaded`]
+    /// if it hasn't after the given duration.
+    BlockWithTimeout(Duration),
+    /// Try again after an exponentially growing delay, plus up to `jitter`
+    /// of random delay to keep clients from retrying in lockstep.
+    /// The request fails with [`ClientError::Overloaded`] after `max_attempts`
+    /// unsuccessful attempts, the first one included.
+    RetryWithBackoff { max_attempts: u32, jitter: Duration },
+}
+
+/// How many requests took each path of the [`OverflowPolicy`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OverflowMetrics {
+    /// Requests that were queued at the first attempt.
+    pub immediate: u64,
+    /// Requests that were queued after waiting for the server to make room.
+    pub blocked: u64,
+    /// Requests that were queued after one or more retries.
+    pub retried: u64,
+    /// Requests that were given up on, because the queue stayed full.
+    pub rejected: u64,
+}
+
+/// The counters behind [`OverflowMetrics`], shared by all the clones of a client.
+#[derive(Default)]
+pub(crate) struct OverflowCounters {
+    immediate: AtomicU64,
+    blocked: AtomicU64,
+    retried: AtomicU64,
+    rejected: AtomicU64,
+}
+
+impl OverflowCounters {
+    pub(crate) fn snapshot(&self) -> OverflowMetrics {
+        OverflowMetrics {
+            immediate: self.immediate.load(Ordering::Relaxed),
+            blocked: self.blocked.load(Ordering::Relaxed),
+            retried: self.retried.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Queue `message`, following `policy` if the queue is full.
+pub(crate) fn send<T>(
+    sender: &SyncSender<T>,
+    message: T,
+    policy: OverflowPolicy,
+    counters: &OverflowCounters,
+) -> Result<(), ClientError> {
+    let message = match sender.try_send(message) {
+        Ok(()) => {
+            counters.immediate.fetch_add(1, Ordering::Relaxed);
+            return Ok(());
+        }
+        Err(TrySendError::Full(message)) => message,
+        Err(TrySendError::Disconnected(_)) => return Err(ServerGone.into()),
+    };
+    let outcome = match policy {
+        OverflowPolicy::FailFast => Err(ClientError::Overloaded),
+        OverflowPolicy::Block => sender
+            .send(message)
+            .map(|()| &counters.blocked)
+            .map_err(|_| ServerGone.into()),
+        OverflowPolicy::BlockWithTimeout(timeout) => {
+            // `SyncSender` can't wait with a timeout, so we poll instead.
+            let deadline = Instant::now() + timeout;
+            retry(sender, message, &counters.blocked, |_| {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                (!remaining.is_zero()).then(|| remaining.min(POLL_INTERVAL))
+            })
+        }
+        OverflowPolicy::RetryWithBackoff {
+            max_attempts,
+            jitter,
+        } => retry(sender, message, &counters.retried, |attempt| {
+            (attempt < max_attempts).then(|| backoff(attempt) + random_up_to(jitter))
+        }),
+    };
+    if let Err(ClientError::Overloaded) = outcome {
+        counters.rejected.fetch_add(1, Ordering::Relaxed);
+    }
+    outcome.map(|counter| {
+        counter.fetch_add(1, Ordering::Relaxed);
+    })
+}
+
+/// Try to queue `message` again until it succeeds or `next_delay`, given the number
+/// of attempts made so far, returns `None`.
+/// `counter` is handed back on success, to record which path was taken.
+fn retry<'a, T>(
+    sender: &SyncSender<T>,
+    mut message: T,
+    counter: &'a AtomicU64,
+    mut next_delay: impl FnMut(u32) -> Option<Duration>,
+) -> Result<&'a AtomicU64, ClientError> {
+    let mut attempts = 1;
+    while let Some(delay) = next_delay(attempts) {
+        sleep(delay);
+        attempts += 1;
+        message = match sender.try_send(message) {
+            Ok(()) => return Ok(counter),
+            Err(TrySendError::Full(message)) => message,
+            Err(TrySendError::Disconnected(_)) => return Err(ServerGone.into()),
+        };
+    }
+    Err(ClientError::Overloaded)
+}
+
+/// The delay to wait for after the `attempt`-th unsuccessful attempt.
+fn backoff(attempt: u32) -> Duration {
+    INITIAL_BACKOFF.saturating_mul(1 << attempt.saturating_sub(1).min(16))
+}
+
+fn random_up_to(max: Duration) -> Duration {
+    if max.is_zero() {
+        return Duration::ZERO;
+    }
+    // Good enough for jitter, without pulling in a dependency.
+    let random = RandomState::new().build_hasher().finish();
+    let max = u64::try_from(max.as_nanos()).unwrap_or(u64::MAX);
+    Duration::from_nanos(random % max)
+}
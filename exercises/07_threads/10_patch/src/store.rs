@@ -1,31 +1,59 @@
-use crate::data::{Status, Ticket, TicketDraft};
+use crate::data::{Operation, OperationOutcome, Status, Ticket, TicketDraft, TicketPatch};
+use crate::events::TicketEvent;
+use crate::{ClientError, ValidationError};
 use std::collections::BTreeMap;
+use std::ops::RangeBounds;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct TicketId(u64);
 
+impl TicketId {
+    /// The shard that owns this ticket, out of `shards`.
+    /// See [`TicketStore::sharded`].
+    pub(crate) fn shard(self, shards: usize) -> usize {
+        (self.0 % shards as u64) as usize
+    }
+}
+
 #[derive(Clone)]
 pub struct TicketStore {
     tickets: BTreeMap<TicketId, Ticket>,
     counter: u64,
+    /// How much `counter` grows by for every new ticket.
+    step: u64,
+}
+
+impl Default for TicketStore {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl TicketStore {
     pub fn new() -> Self {
+        Self::sharded(0, 1)
+    }
+
+    /// An empty store for shard `shard` out of `shards`.
+    /// Its ids are `shard`, `shard + shards`, `shard + 2 * shards`, and so on,
+    /// so that ids never collide across shards.
+    pub fn sharded(shard: usize, shards: usize) -> Self {
         Self {
             tickets: BTreeMap::new(),
-            counter: 0,
+            counter: shard as u64,
+            step: shards as u64,
         }
     }
 
     pub fn add_ticket(&mut self, ticket: TicketDraft) -> TicketId {
         let id = TicketId(self.counter);
-        self.counter += 1;
+        self.counter += self.step;
         let ticket = Ticket {
             id,
             title: ticket.title,
             description: ticket.description,
             status: Status::ToDo,
+            version: 0,
         };
         self.tickets.insert(id, ticket);
         id
@@ -38,4 +66,127 @@ impl TicketStore {
     pub fn get_mut(&mut self, id: TicketId) -> Option<&mut Ticket> {
         self.tickets.get_mut(&id)
     }
+
+    /// The tickets whose id falls within `range`, in id order.
+    pub fn range(&self, range: impl RangeBounds<TicketId>) -> Vec<Ticket> {
+        self.tickets.range(range).map(|(_, t)| t.clone()).collect()
+    }
+
+    pub fn remove(&mut self, id: TicketId) -> Option<Ticket> {
+        self.tickets.remove(&id)
+    }
+
+    /// Apply `patch` to the ticket it targets, returning the corresponding
+    /// [`TicketEvent::Updated`].
+    ///
+    /// The patch is rejected with [`ValidationError::Conflict`] if the ticket
+    /// has been modified since `patch.expected_version`.
+    pub fn update(&mut self, patch: TicketPatch) -> Result<TicketEvent, ClientError> {
+        let ticket = self
+            .tickets
+            .get_mut(&patch.id)
+            .ok_or(ClientError::NotFound(patch.id))?;
+        if ticket.version != patch.expected_version {
+            return Err(ValidationError::Conflict {
+                current: ticket.clone(),
+            }
+            .into());
+        }
+        let before = ticket.clone();
+        if let Some(t) = patch.title {
+            ticket.title = t;
+        }
+        if let Some(d) = patch.description {
+            ticket.description = d;
+        }
+        if let Some(s) = patch.status {
+            ticket.status = s;
+        }
+        ticket.version += 1;
+        Ok(TicketEvent::Updated {
+            before,
+            after: ticket.clone(),
+        })
+    }
+
+    /// Apply all `operations`, in order, or none of them.
+    ///
+    /// Each change is recorded in an undo log as it's applied: if an operation
+    /// fails, the log is replayed backwards to restore the store as it was.
+    /// On success, the outcome of each operation is returned together with the
+    /// events describing the changes, in the order they were applied.
+    /// On failure, the index of the offending operation is returned alongside its error.
+    pub fn apply_batch(
+        &mut self,
+        operations: Vec<Operation>,
+    ) -> Result<(Vec<OperationOutcome>, Vec<TicketEvent>), (usize, ClientError)> {
+        let counter = self.counter;
+        let mut undo_log = Vec::new();
+        let mut outcomes = Vec::with_capacity(operations.len());
+        let mut events = Vec::new();
+        for (index, operation) in operations.into_iter().enumerate() {
+            match self.apply(operation, &mut undo_log, &mut events) {
+                Ok(outcome) => outcomes.push(outcome),
+                Err(e) => {
+                    for undo in undo_log.into_iter().rev() {
+                        match undo {
+                            Undo::Remove(id) => {
+                                self.tickets.remove(&id);
+                            }
+                            Undo::Restore(ticket) => {
+                                self.tickets.insert(ticket.id, ticket);
+                            }
+                        }
+                    }
+                    self.counter = counter;
+                    return Err((index, e));
+                }
+            }
+        }
+        Ok((outcomes, events))
+    }
+
+    /// Apply a single operation of a batch, recording how to revert it in `undo_log`.
+    fn apply(
+        &mut self,
+        operation: Operation,
+        undo_log: &mut Vec<Undo>,
+        events: &mut Vec<TicketEvent>,
+    ) -> Result<OperationOutcome, ClientError> {
+        let outcome = match operation {
+            Operation::Insert(draft) => {
+                let id = self.add_ticket(draft);
+                undo_log.push(Undo::Remove(id));
+                events.push(TicketEvent::Created(self.tickets[&id].clone()));
+                OperationOutcome::Inserted(id)
+            }
+            Operation::Get(id) => {
+                let ticket = self.get(id).ok_or(ClientError::NotFound(id))?;
+                OperationOutcome::Got(ticket.clone())
+            }
+            Operation::Update(patch) => {
+                let event = self.update(patch)?;
+                if let TicketEvent::Updated { before, .. } = &event {
+                    undo_log.push(Undo::Restore(before.clone()));
+                }
+                events.push(event);
+                OperationOutcome::Updated
+            }
+            Operation::Remove(id) => {
+                let removed = self.remove(id).ok_or(ClientError::NotFound(id))?;
+                undo_log.push(Undo::Restore(removed.clone()));
+                events.push(TicketEvent::Removed(removed.clone()));
+                OperationOutcome::Removed(removed)
+            }
+        };
+        Ok(outcome)
+    }
+}
+
+/// How to revert one change made by a batch, see [`TicketStore::apply_batch`].
+enum Undo {
+    /// The ticket was inserted.
+    Remove(TicketId),
+    /// The ticket was updated or removed: put this version back.
+    Restore(Ticket),
 }
@@ -1,17 +1,16 @@
-use patch::data::{Status, TicketDraft, TicketPatch};
-use patch::launch;
-use ticket_fields::test_helpers::{ticket_description, ticket_title};
+mod common;
+
+use common::draft;
+use patch::data::{OperationOutcome, Status, TicketPatch};
+use patch::{launch, ClientError, TransactionError, ValidationError};
 
 #[test]
 fn works() {
-    let client = launch(5);
-    let draft = TicketDraft {
-        title: ticket_title(),
-        description: ticket_description(),
-    };
+    let (client, _server) = launch(5);
+    let draft = draft();
     let ticket_id = client.insert(draft.clone()).unwrap();
 
-    let ticket = client.get(ticket_id).unwrap().unwrap();
+    let ticket = client.get(ticket_id).unwrap();
     assert_eq!(ticket_id, ticket.id);
     assert_eq!(ticket.status, Status::ToDo);
     assert_eq!(ticket.title, draft.title);
@@ -19,13 +18,184 @@ fn works() {
 
     let patch = TicketPatch {
         id: ticket_id,
+        expected_version: ticket.version,
         title: None,
         description: None,
         status: Some(Status::InProgress),
     };
     client.update(patch).unwrap();
 
-    let ticket = client.get(ticket_id).unwrap().unwrap();
+    let ticket = client.get(ticket_id).unwrap();
     assert_eq!(ticket.id, ticket_id);
     assert_eq!(ticket.status, Status::InProgress);
 }
+
+#[test]
+fn stale_updates_are_rejected() {
+    let (client, _server) = launch(5);
+    let ticket_id = client.insert(draft()).unwrap();
+    let version = client.get(ticket_id).unwrap().version;
+
+    let first = TicketPatch {
+        id: ticket_id,
+        expected_version: version,
+        title: None,
+        description: None,
+        status: Some(Status::InProgress),
+    };
+    client.update(first).unwrap();
+
+    // Built against the same version as `first`, which has already been applied.
+    let second = TicketPatch {
+        id: ticket_id,
+        expected_version: version,
+        title: None,
+        description: None,
+        status: Some(Status::Done),
+    };
+    match client.update(second) {
+        Err(ClientError::Validation(ValidationError::Conflict { current })) => {
+            assert_eq!(current.status, Status::InProgress);
+            assert_eq!(current.version, version + 1);
+        }
+        other => panic!("Expected a conflict, got {:?}", other),
+    }
+
+    let ticket = client.get(ticket_id).unwrap();
+    assert_eq!(ticket.status, Status::InProgress);
+}
+
+#[test]
+fn transactions_are_applied_together() {
+    let (client, _server) = launch(5);
+    let draft = draft();
+
+    let outcomes = client
+        .transaction(|tx| {
+            for _ in 0..3 {
+                tx.insert(draft.clone());
+            }
+        })
+        .unwrap();
+    assert_eq!(outcomes.len(), 3);
+
+    for outcome in outcomes {
+        let OperationOutcome::Inserted(id) = outcome else {
+            panic!("Expected an insertion, got {:?}", outcome);
+        };
+        let ticket = client.get(id).unwrap();
+        assert_eq!(ticket.title, draft.title);
+    }
+}
+
+#[test]
+fn failed_transactions_are_rolled_back() {
+    let (client, _server) = launch(5);
+    let ticket_id = client.insert(draft()).unwrap();
+    let version = client.get(ticket_id).unwrap().version;
+
+    let patch = TicketPatch {
+        id: ticket_id,
+        expected_version: version,
+        title: None,
+        description: None,
+        status: Some(Status::InProgress),
+    };
+    let result = client.transaction(|tx| {
+        tx.update(patch.clone());
+        // Stale: the first operation has already bumped the version.
+        tx.update(patch.clone());
+    });
+    match result {
+        Err(TransactionError::Aborted {
+            index: 1,
+            source: ClientError::Validation(ValidationError::Conflict { .. }),
+        }) => {}
+        other => panic!("Expected the second operation to fail, got {:?}", other),
+    }
+
+    let ticket = client.get(ticket_id).unwrap();
+    assert_eq!(ticket.status, Status::ToDo);
+    assert_eq!(ticket.version, version);
+}
+
+#[test]
+fn rolled_back_transactions_leave_no_trace() {
+    let (client, _server) = launch(5);
+    let draft = draft();
+    let kept = client.insert(draft.clone()).unwrap();
+    let removed = client.insert(draft.clone()).unwrap();
+    let before = client.range(..).unwrap();
+
+    let result = client.transaction(|tx| {
+        tx.insert(draft.clone());
+        tx.update(TicketPatch {
+            id: kept,
+            expected_version: 0,
+            title: None,
+            description: None,
+            status: Some(Status::Done),
+        });
+        tx.remove(removed);
+        // Already removed by the previous operation.
+        tx.get(removed);
+    });
+    assert!(matches!(
+        result,
+        Err(TransactionError::Aborted {
+            index: 3,
+            source: ClientError::NotFound(_),
+        })
+    ));
+    assert_eq!(client.range(..).unwrap(), before);
+}
+
+#[test]
+fn shutdown_drains_pending_commands() {
+    let (client, server) = launch(16);
+    let draft = draft();
+    let pending: Vec<_> = (0..10)
+        .map(|_| {
+            let client = client.clone();
+            let draft = draft.clone();
+            std::thread::spawn(move || client.insert(draft))
+        })
+        .collect();
+    // Wait for every insertion to be queued, so that they are all ahead of the shutdown.
+    while client.overflow_metrics().immediate < 10 {
+        std::thread::yield_now();
+    }
+
+    server.shutdown().unwrap();
+
+    for insertion in pending {
+        assert!(insertion.join().unwrap().is_ok());
+    }
+    assert!(matches!(
+        client.insert(draft),
+        Err(ClientError::Disconnected)
+    ));
+}
+
+#[test]
+fn missing_tickets_are_reported() {
+    let (client, _server) = launch(5);
+    let ticket_id = client.insert(draft()).unwrap();
+    client.remove(ticket_id).unwrap();
+
+    assert!(matches!(
+        client.get(ticket_id),
+        Err(ClientError::NotFound(id)) if id == ticket_id
+    ));
+    let patch = TicketPatch {
+        id: ticket_id,
+        expected_version: 0,
+        title: None,
+        description: None,
+        status: Some(Status::Done),
+    };
+    assert!(matches!(
+        client.update(patch),
+        Err(ClientError::NotFound(id)) if id == ticket_id
+    ));
+}
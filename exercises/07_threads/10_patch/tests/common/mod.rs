@@ -0,0 +1,9 @@
+use patch::data::TicketDraft;
+use ticket_fields::test_helpers::{ticket_description, ticket_title};
+
+pub fn draft() -> TicketDraft {
+    TicketDraft {
+        title: ticket_title(),
+        description: ticket_description(),
+    }
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use common::draft;
+use patch::data::{Status, TicketPatch};
+use patch::events::{OnFull, TicketEvent, TicketFilter};
+use patch::launch;
+use std::sync::mpsc::TryRecvError;
+
+#[test]
+fn subscribers_see_every_change() {
+    let (client, _server) = launch(5);
+    let events = client.subscribe(TicketFilter::all()).unwrap();
+
+    let ticket_id = client.insert(draft()).unwrap();
+    let created = client.get(ticket_id).unwrap();
+    client
+        .update(TicketPatch {
+            id: ticket_id,
+            expected_version: created.version,
+            title: None,
+            description: None,
+            status: Some(Status::Done),
+        })
+        .unwrap();
+    let updated = client.remove(ticket_id).unwrap();
+
+    assert_eq!(
+        events.recv().unwrap(),
+        TicketEvent::Created(created.clone())
+    );
+    assert_eq!(
+        events.recv().unwrap(),
+        TicketEvent::Updated {
+            before: created,
+            after: updated.clone(),
+        }
+    );
+    assert_eq!(events.recv().unwrap(), TicketEvent::Removed(updated));
+    assert_eq!(events.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn filters_are_applied() {
+    let (client, _server) = launch(5);
+    let first = client.insert(draft()).unwrap();
+    let events = client
+        .subscribe(TicketFilter {
+            id: Some(first),
+            status: None,
+        })
+        .unwrap();
+
+    client.insert(draft()).unwrap();
+    client.remove(first).unwrap();
+
+    match events.recv().unwrap() {
+        TicketEvent::Removed(ticket) => assert_eq!(ticket.id, first),
+        other => panic!("Expected a removal, got {:?}", other),
+    }
+    assert_eq!(events.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn slow_subscribers_are_disconnected() {
+    let (client, _server) = launch(5);
+    let events = client
+        .subscribe_with(TicketFilter::all(), 1, OnFull::Disconnect)
+        .unwrap();
+
+    client.insert(draft()).unwrap();
+    client.insert(draft()).unwrap();
+
+    assert!(matches!(events.recv().unwrap(), TicketEvent::Created(_)));
+    assert_eq!(events.try_recv(), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn slow_subscribers_can_skip_events_instead() {
+    let (client, _server) = launch(5);
+    let events = client
+        .subscribe_with(TicketFilter::all(), 1, OnFull::DropEvent)
+        .unwrap();
+
+    let first = client.insert(draft()).unwrap();
+    client.insert(draft()).unwrap();
+    match events.recv().unwrap() {
+        TicketEvent::Created(ticket) => assert_eq!(ticket.id, first),
+        other => panic!("Expected a creation, got {:?}", other),
+    }
+
+    // The subscription survived the overflow.
+    let third = client.insert(draft()).unwrap();
+    match events.recv().unwrap() {
+        TicketEvent::Created(ticket) => assert_eq!(ticket.id, third),
+        other => panic!("Expected a creation, got {:?}", other),
+    }
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use common::draft;
+use patch::overflow::{OverflowMetrics, OverflowPolicy};
+use patch::{launch_with_policy, ClientError, TicketStoreClient};
+use std::time::Duration;
+
+const N_CLIENTS: u64 = 8;
+const N_REQUESTS: u64 = 50;
+
+/// Hammer the server from several threads, to fill its queue,
+/// and return how many requests failed with `ClientError::Overloaded`.
+fn hammer(client: &TicketStoreClient) -> u64 {
+    let draft = draft();
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..N_CLIENTS)
+            .map(|_| {
+                let client = client.clone();
+                let draft = draft.clone();
+                scope.spawn(move || {
+                    let mut overloaded = 0;
+                    for _ in 0..N_REQUESTS {
+                        match client.insert(draft.clone()) {
+                            Ok(_) => {}
+                            Err(ClientError::Overloaded) => overloaded += 1,
+                            Err(e) => panic!("Unexpected error: {e}"),
+                        }
+                    }
+                    overloaded
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).sum()
+    })
+}
+
+fn total(metrics: OverflowMetrics) -> u64 {
+    metrics.immediate + metrics.blocked + metrics.retried + metrics.rejected
+}
+
+#[test]
+fn fail_fast() {
+    let (client, _server) = launch_with_policy(1, OverflowPolicy::FailFast);
+    let overloaded = hammer(&client);
+
+    let metrics = client.overflow_metrics();
+    assert_eq!(total(metrics), N_CLIENTS * N_REQUESTS);
+    assert_eq!(metrics.rejected, overloaded);
+    assert_eq!(metrics.blocked + metrics.retried, 0);
+}
+
+#[test]
+fn block() {
+    let (client, _server) = launch_with_policy(1, OverflowPolicy::Block);
+    assert_eq!(hammer(&client), 0);
+
+    let metrics = client.overflow_metrics();
+    assert_eq!(metrics.immediate + metrics.blocked, N_CLIENTS * N_REQUESTS);
+}
+
+#[test]
+fn block_with_timeout() {
+    let policy = OverflowPolicy::BlockWithTimeout(Duration::from_secs(10));
+    let (client, _server) = launch_with_policy(1, policy);
+    assert_eq!(hammer(&client), 0);
+
+    let metrics = client.overflow_metrics();
+    assert_eq!(metrics.immediate + metrics.blocked, N_CLIENTS * N_REQUESTS);
+}
+
+#[test]
+fn retry_with_backoff() {
+    let policy = OverflowPolicy::RetryWithBackoff {
+        max_attempts: 3,
+        jitter: Duration::from_millis(1),
+    };
+    let (client, _server) = launch_with_policy(1, policy);
+    let overloaded = hammer(&client);
+
+    let metrics = client.overflow_metrics();
+    assert_eq!(total(metrics), N_CLIENTS * N_REQUESTS);
+    assert_eq!(metrics.rejected, overloaded);
+    assert_eq!(metrics.blocked, 0);
+}
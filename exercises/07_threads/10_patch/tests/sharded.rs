@@ -0,0 +1,100 @@
+mod common;
+
+use common::draft;
+use patch::data::{Status, TicketPatch};
+use patch::events::{OnFull, TicketEvent, TicketFilter};
+use patch::{launch_sharded, ClientError, TransactionError, ValidationError};
+use std::collections::BTreeSet;
+use std::sync::mpsc::TryRecvError;
+
+#[test]
+fn ids_are_unique_across_shards() {
+    let (client, _server) = launch_sharded(4, 5);
+    let ids: Vec<_> = (0..20).map(|_| client.insert(draft()).unwrap()).collect();
+    assert_eq!(ids.iter().collect::<BTreeSet<_>>().len(), ids.len());
+
+    for id in ids {
+        let ticket = client.get(id).unwrap();
+        client
+            .update(TicketPatch {
+                id,
+                expected_version: ticket.version,
+                title: None,
+                description: None,
+                status: Some(Status::Done),
+            })
+            .unwrap();
+        assert_eq!(client.get(id).unwrap().status, Status::Done);
+    }
+}
+
+#[test]
+fn range_scans_are_merged_in_id_order() {
+    let (client, _server) = launch_sharded(3, 5);
+    let mut ids: Vec<_> = (0..10).map(|_| client.insert(draft()).unwrap()).collect();
+    ids.sort();
+
+    let all: Vec<_> = client.range(..).unwrap().iter().map(|t| t.id).collect();
+    assert_eq!(all, ids);
+
+    let some: Vec<_> = client
+        .range(ids[2]..=ids[6])
+        .unwrap()
+        .iter()
+        .map(|t| t.id)
+        .collect();
+    assert_eq!(some, ids[2..=6]);
+}
+
+#[test]
+fn transactions_cannot_span_shards() {
+    let (client, _server) = launch_sharded(2, 5);
+    let first = client.insert(draft()).unwrap();
+    let second = client.insert(draft()).unwrap();
+
+    let result = client.transaction(|tx| {
+        tx.get(first);
+        tx.get(second);
+    });
+    assert!(matches!(
+        result,
+        Err(TransactionError::Client(ClientError::Validation(
+            ValidationError::CrossShard
+        )))
+    ));
+
+    let outcomes = client
+        .transaction(|tx| {
+            tx.get(first);
+            tx.insert(draft());
+        })
+        .unwrap();
+    assert_eq!(outcomes.len(), 2);
+}
+
+#[test]
+fn shutdown_stops_every_shard() {
+    let (client, server) = launch_sharded(3, 5);
+    let ids: Vec<_> = (0..3).map(|_| client.insert(draft()).unwrap()).collect();
+
+    server.shutdown().unwrap();
+
+    for id in ids {
+        assert!(matches!(client.get(id), Err(ClientError::Disconnected)));
+    }
+}
+
+#[test]
+fn slow_subscribers_are_disconnected_from_every_shard() {
+    let (client, _server) = launch_sharded(2, 5);
+    let events = client
+        .subscribe_with(TicketFilter::all(), 1, OnFull::Disconnect)
+        .unwrap();
+
+    // Insertions alternate between shards: the second one overflows the buffer.
+    client.insert(draft()).unwrap();
+    client.insert(draft()).unwrap();
+
+    assert!(matches!(events.recv().unwrap(), TicketEvent::Created(_)));
+    assert_eq!(events.try_recv(), Err(TryRecvError::Disconnected));
+}
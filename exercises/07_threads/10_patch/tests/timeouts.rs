@@ -0,0 +1,33 @@
+mod common;
+
+use common::draft;
+use patch::events::TicketFilter;
+use patch::{launch, ClientError};
+use std::sync::mpsc::TryRecvError;
+use std::time::{Duration, Instant};
+
+#[test]
+fn requests_within_their_deadline_succeed() {
+    let (client, _server) = launch(5);
+    let client = client.with_timeout(Duration::from_secs(5));
+
+    let ticket_id = client.insert(draft()).unwrap();
+    let ticket = client
+        .get_timeout(ticket_id, Duration::from_secs(5))
+        .unwrap();
+    assert_eq!(ticket.id, ticket_id);
+}
+
+#[test]
+fn expired_requests_are_skipped() {
+    let (client, _server) = launch(5);
+    let events = client.subscribe(TicketFilter::all()).unwrap();
+
+    let result = client.insert_with_deadline(draft(), Instant::now());
+    assert!(matches!(result, Err(ClientError::Timeout)));
+
+    // The server never got to insert the ticket.
+    client.insert(draft()).unwrap();
+    assert!(events.recv().is_ok());
+    assert_eq!(events.try_recv(), Err(TryRecvError::Empty));
+}
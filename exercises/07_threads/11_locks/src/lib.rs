@@ -1,8 +1,9 @@
 // TODO: Fill in the missing methods for `TicketStore`.
 //  Notice how we no longer need a separate update command: `Get` now returns a handle to the ticket
 //  which allows the caller to both modify and read the ticket.
-use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
 use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
 
 pub use crate::data::{Ticket, TicketDraft};
 pub use crate::store::{TicketId, TicketStore};
@@ -16,48 +17,112 @@ pub struct TicketStoreClient {
 }
 
 impl TicketStoreClient {
-    pub fn insert(&self, draft: TicketDraft) -> Result<TicketId, OverloadedError> {
+    fn send(&self, command: Command) -> Result<(), ClientError> {
+        self.sender.try_send(command).map_err(|e| match e {
+            TrySendError::Full(_) => ClientError::Overloaded,
+            TrySendError::Disconnected(_) => ServerGone.into(),
+        })
+    }
+
+    pub fn insert(&self, draft: TicketDraft) -> Result<TicketId, ClientError> {
         let (response_sender, response_receiver) = sync_channel(1);
-        self.sender
-            .try_send(Command::Insert {
-                draft,
-                response_channel: response_sender,
-            })
-            .map_err(|_| OverloadedError)?;
-        Ok(response_receiver.recv().unwrap())
+        self.send(Command::Insert {
+            draft,
+            response_channel: response_sender,
+        })?;
+        response_receiver.recv().map_err(|_| ServerGone)?
     }
 
-    pub fn get(&self, id: TicketId) -> Result<Option<Arc<Mutex<Ticket>>>, OverloadedError> {
+    pub fn get(&self, id: TicketId) -> Result<Arc<Mutex<Ticket>>, ClientError> {
         let (response_sender, response_receiver) = sync_channel(1);
-        self.sender
-            .try_send(Command::Get {
-                id,
-                response_channel: response_sender,
-            })
-            .map_err(|_| OverloadedError)?;
-        Ok(response_receiver.recv().unwrap())
+        self.send(Command::Get {
+            id,
+            response_channel: response_sender,
+        })?;
+        response_receiver.recv().map_err(|_| ServerGone)?
+    }
+}
+
+/// Everything that can go wrong when talking to the server.
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("The store is overloaded")]
+    Overloaded,
+    /// See [`ServerGone`].
+    #[error("The server is no longer running")]
+    Disconnected,
+    #[error("There is no ticket with id {0:?}")]
+    NotFound(TicketId),
+}
+
+/// The server has stopped, either because it was shut down or because it panicked.
+/// Clients report it as [`ClientError::Disconnected`].
+#[derive(Debug, thiserror::Error)]
+#[error("The server is no longer running")]
+pub struct ServerGone;
+
+impl From<ServerGone> for ClientError {
+    fn from(_: ServerGone) -> Self {
+        ClientError::Disconnected
     }
 }
 
+/// The server thread panicked. The panic message is included, if there was one.
 #[derive(Debug, thiserror::Error)]
-#[error("The store is overloaded")]
-pub struct OverloadedError;
+#[error("The server panicked: {0}")]
+pub struct ServerPanicked(pub String);
+
+/// Controls the lifecycle of a server started with [`launch`].
+pub struct ServerHandle {
+    sender: SyncSender<Command>,
+    thread: JoinHandle<()>,
+}
+
+impl ServerHandle {
+    /// Stop the server once it has processed the commands that are already queued,
+    /// then wait for it to exit.
+    /// Commands sent after the shutdown request fail with [`ServerGone`].
+    pub fn shutdown(self) -> Result<(), ServerPanicked> {
+        // If the server is already gone, `join` will tell us why.
+        let _ = self.sender.send(Command::Shutdown);
+        self.join()
+    }
+
+    /// Wait for the server to exit, which happens when every client has been dropped.
+    pub fn join(self) -> Result<(), ServerPanicked> {
+        let ServerHandle { sender, thread } = self;
+        drop(sender);
+        thread.join().map_err(|payload| {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            ServerPanicked(message)
+        })
+    }
+}
 
-pub fn launch(capacity: usize) -> TicketStoreClient {
+pub fn launch(capacity: usize) -> (TicketStoreClient, ServerHandle) {
     let (sender, receiver) = sync_channel(capacity);
-    std::thread::spawn(move || server(receiver));
-    TicketStoreClient { sender }
+    let thread = std::thread::spawn(move || server(receiver));
+    let handle = ServerHandle {
+        sender: sender.clone(),
+        thread,
+    };
+    (TicketStoreClient { sender }, handle)
 }
 
 enum Command {
     Insert {
         draft: TicketDraft,
-        response_channel: SyncSender<TicketId>,
+        response_channel: SyncSender<Result<TicketId, ClientError>>,
     },
     Get {
         id: TicketId,
-        response_channel: SyncSender<Option<Arc<Mutex<Ticket>>>>,
+        response_channel: SyncSender<Result<Arc<Mutex<Ticket>>, ClientError>>,
     },
+    Shutdown,
 }
 
 fn server(receiver: Receiver<Command>) {
@@ -69,17 +134,18 @@ fn server(receiver: Receiver<Command>) {
                 response_channel,
             }) => {
                 let id = store.add_ticket(draft);
-                let _ = response_channel.send(id);
+                let _ = response_channel.send(Ok(id));
             }
             Ok(Command::Get {
                 id,
                 response_channel,
             }) => {
-                let ticket = store.get(id);
+                let ticket = store.get(id).ok_or(ClientError::NotFound(id));
                 let _ = response_channel.send(ticket);
             }
-            Err(_) => {
-                // No more senders – we can exit.
+            Ok(Command::Shutdown) | Err(_) => {
+                // We've been asked to stop, or there are no more senders:
+                // either way, we can safely break and shut down the server.
                 break;
             }
         }
@@ -4,12 +4,11 @@ use crate::data::{Status, Ticket, TicketDraft};
 use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex};
 
-
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct TicketId(u64);
 
 /// The actual store that lives inside the server thread.
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct TicketStore {
     tickets: BTreeMap<TicketId, Arc<Mutex<Ticket>>>,
     counter: u64,
@@ -1,17 +1,17 @@
 use locks::data::{Status, TicketDraft};
-use locks::launch;
+use locks::{launch, ClientError};
 use ticket_fields::test_helpers::{ticket_description, ticket_title};
 
 #[test]
 fn works() {
-    let client = launch(5);
+    let (client, _server) = launch(5);
     let draft = TicketDraft {
         title: ticket_title(),
         description: ticket_description(),
     };
     let ticket_id = client.insert(draft.clone()).unwrap();
 
-    let ticket = client.get(ticket_id).unwrap().unwrap();
+    let ticket = client.get(ticket_id).unwrap();
     {
         let mut ticket = ticket.lock().unwrap();
         assert_eq!(ticket_id, ticket.id);
@@ -22,10 +22,27 @@ fn works() {
         ticket.status = Status::InProgress;
     }
 
-    let ticket = client.get(ticket_id).unwrap().unwrap();
+    let ticket = client.get(ticket_id).unwrap();
     {
         let ticket = ticket.lock().unwrap();
         assert_eq!(ticket_id, ticket.id);
         assert_eq!(ticket.status, Status::InProgress);
     }
 }
+
+#[test]
+fn shutdown() {
+    let (client, server) = launch(5);
+    let draft = TicketDraft {
+        title: ticket_title(),
+        description: ticket_description(),
+    };
+    let ticket_id = client.insert(draft).unwrap();
+
+    server.shutdown().unwrap();
+
+    assert!(matches!(
+        client.get(ticket_id),
+        Err(ClientError::Disconnected)
+    ));
+}
@@ -1,7 +1,8 @@
 // TODO: Replace `Mutex` with `RwLock` in the `TicketStore` struct and
 //  all other relevant places to allow multiple readers to access the ticket store concurrently.
-use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
 use std::sync::Arc;
+use std::thread::JoinHandle;
 
 use crate::data::{Ticket, TicketDraft};
 use crate::store::{TicketId, TicketStore};
@@ -16,57 +17,118 @@ pub struct TicketStoreClient {
 }
 
 impl TicketStoreClient {
+    fn send(&self, command: Command) -> Result<(), ClientError> {
+        self.sender.try_send(command).map_err(|e| match e {
+            TrySendError::Full(_) => ClientError::Overloaded,
+            TrySendError::Disconnected(_) => ServerGone.into(),
+        })
+    }
+
     /// Insert a new ticket.  Returns the new ticket ID.
-    pub fn insert(&self, draft: TicketDraft) -> Result<TicketId, OverloadedError> {
+    pub fn insert(&self, draft: TicketDraft) -> Result<TicketId, ClientError> {
         let (response_sender, response_receiver) = sync_channel(1);
-        self.sender
-            .try_send(Command::Insert {
-                draft,
-                response_channel: response_sender,
-            })
-            .map_err(|_| OverloadedError)?;
-        Ok(response_receiver.recv().unwrap())
+        self.send(Command::Insert {
+            draft,
+            response_channel: response_sender,
+        })?;
+        response_receiver.recv().map_err(|_| ServerGone)?
     }
 
     /// Get a ticket by ID.  Returns an `Arc<RwLock<Ticket>>` so the caller can read or
-    /// modify the ticket.  The `Option` is `None` if the ID is unknown.
-    pub fn get(
-        &self,
-        id: TicketId,
-    ) -> Result<Option<Arc<std::sync::RwLock<Ticket>>>, OverloadedError> {
+    /// modify the ticket.  Fails with [`ClientError::NotFound`] if the ID is unknown.
+    pub fn get(&self, id: TicketId) -> Result<Arc<std::sync::RwLock<Ticket>>, ClientError> {
         let (response_sender, response_receiver) = sync_channel(1);
-        self.sender
-            .try_send(Command::Get {
-                id,
-                response_channel: response_sender,
-            })
-            .map_err(|_| OverloadedError)?;
-        Ok(response_receiver.recv().unwrap())
+        self.send(Command::Get {
+            id,
+            response_channel: response_sender,
+        })?;
+        response_receiver.recv().map_err(|_| ServerGone)?
     }
 }
 
-/// Error returned when the channel buffer is full.
+/// Everything that can go wrong when talking to the server.
 #[derive(Debug, thiserror::Error)]
-#[error("The store is overloaded")]
-pub struct OverloadedError;
+pub enum ClientError {
+    #[error("The store is overloaded")]
+    Overloaded,
+    /// See [`ServerGone`].
+    #[error("The server is no longer running")]
+    Disconnected,
+    #[error("There is no ticket with id {0:?}")]
+    NotFound(TicketId),
+}
+
+/// The server has stopped, either because it was shut down or because it panicked.
+/// Clients report it as [`ClientError::Disconnected`].
+#[derive(Debug, thiserror::Error)]
+#[error("The server is no longer running")]
+pub struct ServerGone;
+
+impl From<ServerGone> for ClientError {
+    fn from(_: ServerGone) -> Self {
+        ClientError::Disconnected
+    }
+}
+
+/// The server thread panicked. The panic message is included, if there was one.
+#[derive(Debug, thiserror::Error)]
+#[error("The server panicked: {0}")]
+pub struct ServerPanicked(pub String);
+
+/// Controls the lifecycle of a server started with [`launch`].
+pub struct ServerHandle {
+    sender: SyncSender<Command>,
+    thread: JoinHandle<()>,
+}
+
+impl ServerHandle {
+    /// Stop the server once it has processed the commands that are already queued,
+    /// then wait for it to exit.
+    /// Commands sent after the shutdown request fail with [`ServerGone`].
+    pub fn shutdown(self) -> Result<(), ServerPanicked> {
+        // If the server is already gone, `join` will tell us why.
+        let _ = self.sender.send(Command::Shutdown);
+        self.join()
+    }
+
+    /// Wait for the server to exit, which happens when every client has been dropped.
+    pub fn join(self) -> Result<(), ServerPanicked> {
+        let ServerHandle { sender, thread } = self;
+        drop(sender);
+        thread.join().map_err(|payload| {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            ServerPanicked(message)
+        })
+    }
+}
 
 /// Launch a new ticket‑store server with a bounded channel capacity.
-/// Returns a client that can be used to send requests.
-pub fn launch(capacity: usize) -> TicketStoreClient {
+/// Returns a client that can be used to send requests, and a handle
+/// to control the lifecycle of the server.
+pub fn launch(capacity: usize) -> (TicketStoreClient, ServerHandle) {
     let (sender, receiver) = sync_channel(capacity);
-    std::thread::spawn(move || server(receiver));
-    TicketStoreClient { sender }
+    let thread = std::thread::spawn(move || server(receiver));
+    let handle = ServerHandle {
+        sender: sender.clone(),
+        thread,
+    };
+    (TicketStoreClient { sender }, handle)
 }
 
 enum Command {
     Insert {
         draft: TicketDraft,
-        response_channel: SyncSender<TicketId>,
+        response_channel: SyncSender<Result<TicketId, ClientError>>,
     },
     Get {
         id: TicketId,
-        response_channel: SyncSender<Option<Arc<std::sync::RwLock<Ticket>>>>,
+        response_channel: SyncSender<Result<Arc<std::sync::RwLock<Ticket>>, ClientError>>,
     },
+    Shutdown,
 }
 
 fn server(receiver: Receiver<Command>) {
@@ -78,17 +140,18 @@ fn server(receiver: Receiver<Command>) {
                 response_channel,
             }) => {
                 let id = store.add_ticket(draft);
-                let _ = response_channel.send(id);
+                let _ = response_channel.send(Ok(id));
             }
             Ok(Command::Get {
                 id,
                 response_channel,
             }) => {
-                let ticket = store.get(id);
+                let ticket = store.get(id).ok_or(ClientError::NotFound(id));
                 let _ = response_channel.send(ticket);
             }
-            Err(_) => {
-                // No more senders – shutdown the server.
+            Ok(Command::Shutdown) | Err(_) => {
+                // We've been asked to stop, or there are no more senders:
+                // either way, we can safely break and shut down the server.
                 break;
             }
         }
@@ -1,17 +1,17 @@
 use rwlock::data::{Status, TicketDraft};
-use rwlock::launch;
+use rwlock::{launch, ClientError};
 use ticket_fields::test_helpers::{ticket_description, ticket_title};
 
 #[test]
 fn works() {
-    let client = launch(5);
+    let (client, _server) = launch(5);
     let draft = TicketDraft {
         title: ticket_title(),
         description: ticket_description(),
     };
     let ticket_id = client.insert(draft.clone()).unwrap();
 
-    let ticket = client.get(ticket_id).unwrap().unwrap();
+    let ticket = client.get(ticket_id).unwrap();
     let lock1 = ticket.read().unwrap();
     {
         let ticket = ticket.read().unwrap();
@@ -23,9 +23,26 @@ fn works() {
 
     drop(lock1);
 
-    let ticket = client.get(ticket_id).unwrap().unwrap();
+    let ticket = client.get(ticket_id).unwrap();
     {
         let mut ticket = ticket.write().unwrap();
         ticket.status = Status::InProgress;
     }
 }
+
+#[test]
+fn shutdown() {
+    let (client, server) = launch(5);
+    let draft = TicketDraft {
+        title: ticket_title(),
+        description: ticket_description(),
+    };
+    let ticket_id = client.insert(draft).unwrap();
+
+    server.shutdown().unwrap();
+
+    assert!(matches!(
+        client.get(ticket_id),
+        Err(ClientError::Disconnected)
+    ));
+}
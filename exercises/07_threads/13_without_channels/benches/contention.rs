@@ -0,0 +1,70 @@
+//! Many threads adding tickets at the same time, to compare a `TicketStore`
+//! behind a global lock with a `ConcurrentTicketStore`.
+//!
+//! The comparison isn't only about locking: `TicketStore` also publishes every new
+//! ticket for its snapshots, which `ConcurrentTicketStore` doesn't support.
+
+use std::sync::{Arc, RwLock};
+use std::thread;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use ticket_fields::test_helpers::{ticket_description, ticket_title};
+use without_channels::concurrent_store::ConcurrentTicketStore;
+use without_channels::data::TicketDraft;
+use without_channels::store::TicketStore;
+
+const TICKETS_PER_WRITER: usize = 1_000;
+
+/// Spawn `writers` threads, each running `write` `TICKETS_PER_WRITER` times.
+///
+/// Drafts are built before calling `write`, so that building them is never
+/// measured while a lock is held.
+fn run_writers(writers: usize, write: impl Fn(TicketDraft) + Sync) {
+    thread::scope(|scope| {
+        for _ in 0..writers {
+            scope.spawn(|| {
+                for _ in 0..TICKETS_PER_WRITER {
+                    let draft = TicketDraft {
+                        title: ticket_title(),
+                        description: ticket_description(),
+                    };
+                    write(draft);
+                }
+            });
+        }
+    });
+}
+
+fn contention(c: &mut Criterion) {
+    let mut group = c.benchmark_group("add_ticket");
+    for writers in [1, 4, 16, 64] {
+        group.bench_with_input(
+            BenchmarkId::new("global_lock", writers),
+            &writers,
+            |b, &writers| {
+                b.iter(|| {
+                    let store = Arc::new(RwLock::new(TicketStore::new()));
+                    run_writers(writers, |draft| {
+                        store.write().unwrap().add_ticket(draft);
+                    });
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("concurrent", writers),
+            &writers,
+            |b, &writers| {
+                b.iter(|| {
+                    let store = ConcurrentTicketStore::new();
+                    run_writers(writers, |draft| {
+                        store.add_ticket(draft);
+                    });
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, contention);
+criterion_main!(benches);
@@ -0,0 +1,68 @@
+//! A ticket store that can be shared between threads without wrapping it in a lock.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+
+use crate::data::{Status, Ticket, TicketDraft};
+use crate::handle::{PoisonPolicy, TicketHandle};
+use crate::store::TicketId;
+
+/// How many shards a store has, unless specified otherwise.
+pub const DEFAULT_SHARDS: usize = 16;
+
+type Shard = RwLock<BTreeMap<TicketId, TicketHandle>>;
+
+/// A variant of [`TicketStore`](crate::store::TicketStore) whose methods only need `&self`.
+///
+/// Tickets are spread across shards, each behind its own lock, and ids come from an
+/// atomic counter: writers only contend with each other when they touch the same shard,
+/// and readers are never blocked by writers working on a different shard.
+pub struct ConcurrentTicketStore {
+    shards: Box<[Shard]>,
+    counter: AtomicU64,
+}
+
+impl Default for ConcurrentTicketStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConcurrentTicketStore {
+    pub fn new() -> Self {
+        Self::with_shards(DEFAULT_SHARDS)
+    }
+
+    /// # Panics
+    ///
+    /// Panics if `shards` is zero.
+    pub fn with_shards(shards: usize) -> Self {
+        assert!(shards > 0, "A store needs at least one shard");
+        Self {
+            shards: (0..shards).map(|_| Shard::default()).collect(),
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    fn shard(&self, id: TicketId) -> &Shard {
+        &self.shards[(id.0 % self.shards.len() as u64) as usize]
+    }
+
+    pub fn add_ticket(&self, ticket: TicketDraft) -> TicketId {
+        let id = TicketId(self.counter.fetch_add(1, Ordering::Relaxed));
+        let ticket = Ticket {
+            id,
+            title: ticket.title,
+            description: ticket.description,
+            status: Status::ToDo,
+        };
+        let ticket = TicketHandle::new(ticket, PoisonPolicy::default(), None);
+        self.shard(id).write().unwrap().insert(id, ticket);
+        id
+    }
+
+    pub fn get(&self, id: TicketId) -> Option<TicketHandle> {
+        self.shard(id).read().unwrap().get(&id).cloned()
+    }
+}
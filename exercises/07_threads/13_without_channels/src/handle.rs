@@ -0,0 +1,207 @@
+//! Shared access to a single ticket, surviving panics in the threads that use it.
+//!
+//! A thread that panics while holding a ticket's write lock poisons it: with a raw
+//! `RwLock`, every later `.read().unwrap()` panics too. A [`TicketHandle`] checks
+//! that the ticket is still consistent instead, and either carries on with it or
+//! quarantines it, depending on its [`PoisonPolicy`].
+
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{
+    Arc, LockResult, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError,
+    TryLockResult,
+};
+
+use crate::data::Ticket;
+use crate::snapshot::Published;
+use crate::store::TicketId;
+use ticket_fields::{TicketDescription, TicketTitle};
+
+/// What to do with a ticket whose lock was poisoned.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PoisonPolicy {
+    /// Keep using the ticket if it's still consistent, quarantine it otherwise.
+    #[default]
+    Recover,
+    /// Always quarantine the ticket.
+    Quarantine,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum HandleError {
+    /// A panic left the ticket in a state that can't be trusted.
+    /// It stays unavailable until it's [restored](TicketHandle::restore).
+    #[error("Ticket {0:?} is quarantined")]
+    Quarantined(TicketId),
+    /// The change would have left the ticket inconsistent, so it wasn't applied.
+    #[error("The change would leave ticket {0:?} inconsistent")]
+    InvariantViolated(TicketId),
+}
+
+/// A cheaply cloned, shared reference to a ticket.
+#[derive(Clone)]
+pub struct TicketHandle(Arc<Inner>);
+
+struct Inner {
+    id: TicketId,
+    ticket: RwLock<Ticket>,
+    policy: PoisonPolicy,
+    quarantined: AtomicBool,
+    /// Where committed versions go, for stores that support snapshots.
+    published: Option<Arc<Published>>,
+}
+
+impl TicketHandle {
+    pub(crate) fn new(
+        ticket: Ticket,
+        policy: PoisonPolicy,
+        published: Option<Arc<Published>>,
+    ) -> Self {
+        Self(Arc::new(Inner {
+            id: ticket.id,
+            ticket: RwLock::new(ticket),
+            policy,
+            quarantined: AtomicBool::new(false),
+            published,
+        }))
+    }
+
+    pub fn id(&self) -> TicketId {
+        self.0.id
+    }
+
+    pub fn is_quarantined(&self) -> bool {
+        self.0.quarantined.load(Ordering::Acquire)
+    }
+
+    /// Lock the ticket for reading.
+    pub fn read(&self) -> Result<RwLockReadGuard<'_, Ticket>, HandleError> {
+        self.check_quarantine()?;
+        self.recover(self.0.ticket.read())
+    }
+
+    /// Apply `change` to the ticket, returning whatever `change` returns.
+    ///
+    /// `change` works on a copy, which only replaces the ticket if it's still
+    /// consistent afterwards: a panic in `change` leaves the ticket untouched.
+    pub fn update<R>(&self, change: impl FnOnce(&mut Ticket) -> R) -> Result<R, HandleError> {
+        let mut ticket = self.write()?;
+        let mut updated = ticket.clone();
+        let output = change(&mut updated);
+        if !self.is_consistent(&updated) {
+            return Err(HandleError::InvariantViolated(self.0.id));
+        }
+        *ticket = updated;
+        self.publish(&ticket);
+        Ok(output)
+    }
+
+    /// Replace the ticket with `ticket`, lifting the quarantine if there was one.
+    pub fn restore(&self, ticket: Ticket) -> Result<(), HandleError> {
+        if !self.is_consistent(&ticket) {
+            return Err(HandleError::InvariantViolated(self.0.id));
+        }
+        let mut current = self
+            .0
+            .ticket
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        *current = ticket;
+        self.publish(&current);
+        self.0.ticket.clear_poison();
+        self.0.quarantined.store(false, Ordering::Release);
+        Ok(())
+    }
+
+    /// A handle to a copy of the ticket, publishing to `published` instead.
+    ///
+    /// A ticket that can't be trusted stays quarantined in the copy, and isn't published.
+    pub(crate) fn duplicate(&self, published: Option<Arc<Published>>) -> Self {
+        let (ticket, quarantined) = match self.read() {
+            Ok(ticket) => (ticket.clone(), false),
+            Err(_) => {
+                let ticket = self.0.ticket.read().unwrap_or_else(PoisonError::into_inner);
+                (ticket.clone(), true)
+            }
+        };
+        let copy = Self::new(ticket, self.0.policy, published);
+        if quarantined {
+            copy.0.quarantined.store(true, Ordering::Release);
+        } else {
+            copy.publish(&copy.0.ticket.read().unwrap_or_else(PoisonError::into_inner));
+        }
+        copy
+    }
+
+    /// Lock the ticket for writing, without the safety net of [`TicketHandle::update`].
+    pub(crate) fn write(&self) -> Result<RwLockWriteGuard<'_, Ticket>, HandleError> {
+        self.check_quarantine()?;
+        self.recover(self.0.ticket.write())
+    }
+
+    /// Like [`TicketHandle::read`], but returns `None` instead of waiting
+    /// if the ticket is locked for writing.
+    pub(crate) fn try_read(&self) -> Result<Option<RwLockReadGuard<'_, Ticket>>, HandleError> {
+        self.check_quarantine()?;
+        self.try_recover(self.0.ticket.try_read())
+    }
+
+    /// Like [`TicketHandle::write`], but returns `None` instead of waiting
+    /// if the ticket is locked.
+    pub(crate) fn try_write(&self) -> Result<Option<RwLockWriteGuard<'_, Ticket>>, HandleError> {
+        self.check_quarantine()?;
+        self.try_recover(self.0.ticket.try_write())
+    }
+
+    /// Make `ticket` visible to the snapshots taken from now on.
+    /// Called with the write lock held, so that versions are published in order.
+    fn publish(&self, ticket: &Ticket) {
+        if let Some(published) = &self.0.published {
+            published.publish([ticket.clone()]);
+        }
+    }
+
+    /// Whether `ticket` can be trusted: it's still the ticket this handle points to,
+    /// and its title and description still pass validation.
+    ///
+    /// A panic midway through a change may have left a field in any state, so the
+    /// fields are checked again rather than trusting their types.
+    /// Every [`Status`](crate::data::Status) is valid for every ticket.
+    fn is_consistent(&self, ticket: &Ticket) -> bool {
+        ticket.id == self.0.id
+            && TicketTitle::try_from(ticket.title.as_str()).is_ok()
+            && TicketDescription::try_from(ticket.description.as_str()).is_ok()
+    }
+
+    fn check_quarantine(&self) -> Result<(), HandleError> {
+        if self.is_quarantined() {
+            return Err(HandleError::Quarantined(self.0.id));
+        }
+        Ok(())
+    }
+
+    fn recover<G: Deref<Target = Ticket>>(&self, result: LockResult<G>) -> Result<G, HandleError> {
+        let guard = match result {
+            Ok(guard) => return Ok(guard),
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if self.0.policy == PoisonPolicy::Recover && self.is_consistent(&guard) {
+            self.0.ticket.clear_poison();
+            Ok(guard)
+        } else {
+            self.0.quarantined.store(true, Ordering::Release);
+            Err(HandleError::Quarantined(self.0.id))
+        }
+    }
+
+    fn try_recover<G: Deref<Target = Ticket>>(
+        &self,
+        result: TryLockResult<G>,
+    ) -> Result<Option<G>, HandleError> {
+        match result {
+            Ok(guard) => Ok(Some(guard)),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Poisoned(poisoned)) => self.recover(Err(poisoned)).map(Some),
+        }
+    }
+}
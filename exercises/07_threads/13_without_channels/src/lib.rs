@@ -3,5 +3,9 @@
 //  that's no longer necessary.
 //  Fix the `todo!()` in the testing code and see how the new design can be used.
 
+pub mod concurrent_store;
 pub mod data;
+pub mod handle;
+pub mod multi_lock;
+pub mod snapshot;
 pub mod store;
@@ -0,0 +1,196 @@
+//! Locking several tickets at once, without risking a deadlock.
+//!
+//! Two threads locking the same tickets in different orders can end up waiting
+//! on each other forever. Locks are therefore always acquired in `TicketId` order,
+//! whatever the order in which they were requested.
+
+use std::collections::BTreeMap;
+use std::ops::Deref;
+use std::sync::{RwLockReadGuard, RwLockWriteGuard};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crate::data::{Status, Ticket};
+use crate::handle::{HandleError, TicketHandle};
+use crate::snapshot::Published;
+use crate::store::TicketId;
+use ticket_fields::{TicketDescription, TicketTitle};
+
+/// How long to wait before trying again, when some of the locks are taken.
+const RETRY_INTERVAL: Duration = Duration::from_millis(1);
+
+/// A ticket to lock, and how.
+pub(crate) type LockRequest<'a> = (TicketId, &'a TicketHandle, Access);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum LockError {
+    #[error("There is no ticket with id {0:?}")]
+    NotFound(TicketId),
+    /// See [`HandleError::Quarantined`].
+    #[error("Ticket {0:?} is quarantined")]
+    Quarantined(TicketId),
+    /// See [`HandleError::InvariantViolated`].
+    #[error("Ticket {0:?} is inconsistent")]
+    InvariantViolated(TicketId),
+    #[error("The tickets could not all be locked in time")]
+    Timeout,
+}
+
+enum TicketGuard<'a> {
+    Read(RwLockReadGuard<'a, Ticket>),
+    Write(RwLockWriteGuard<'a, Ticket>),
+}
+
+/// Holds the locks on a set of tickets, releasing all of them when dropped.
+pub struct MultiGuard<'a> {
+    guards: BTreeMap<TicketId, TicketGuard<'a>>,
+    published: &'a Published,
+}
+
+impl MultiGuard<'_> {
+    /// The ticket with the given `id`, if it's one of the locked tickets.
+    pub fn get(&self, id: TicketId) -> Option<&Ticket> {
+        match self.guards.get(&id)? {
+            TicketGuard::Read(guard) => Some(guard),
+            TicketGuard::Write(guard) => Some(guard),
+        }
+    }
+
+    /// The ticket with the given `id`, if it's one of the tickets locked for writing.
+    pub fn get_mut(&mut self, id: TicketId) -> Option<TicketMut<'_>> {
+        match self.guards.get_mut(&id)? {
+            TicketGuard::Read(_) => None,
+            TicketGuard::Write(guard) => Some(TicketMut(guard)),
+        }
+    }
+}
+
+/// A ticket locked for writing.
+///
+/// Every field but the id can be changed: a ticket whose id doesn't match
+/// its place in the store would be quarantined.
+pub struct TicketMut<'a>(&'a mut Ticket);
+
+impl TicketMut<'_> {
+    pub fn set_title(&mut self, title: TicketTitle) {
+        self.0.title = title;
+    }
+
+    pub fn set_description(&mut self, description: TicketDescription) {
+        self.0.description = description;
+    }
+
+    pub fn set_status(&mut self, status: Status) {
+        self.0.status = status;
+    }
+}
+
+impl Deref for TicketMut<'_> {
+    type Target = Ticket;
+
+    fn deref(&self) -> &Ticket {
+        self.0
+    }
+}
+
+impl Drop for MultiGuard<'_> {
+    /// Publish the tickets locked for writing, before releasing any lock.
+    fn drop(&mut self) {
+        // A panic may have left the tickets half-modified:
+        // they are checked the next time they're locked instead.
+        if std::thread::panicking() {
+            return;
+        }
+        let written = self.guards.values().filter_map(|guard| match guard {
+            TicketGuard::Read(_) => None,
+            TicketGuard::Write(guard) => Some(Ticket::clone(guard)),
+        });
+        self.published.publish(written);
+    }
+}
+
+/// Lock every ticket in `requests`, waiting for as long as it takes.
+///
+/// A ticket requested more than once is locked once, for writing
+/// if any of the requests asked for it.
+pub(crate) fn lock_all<'a>(
+    requests: Vec<LockRequest<'a>>,
+    published: &'a Published,
+) -> Result<MultiGuard<'a>, LockError> {
+    let mut guards = BTreeMap::new();
+    for (id, handle, access) in in_lock_order(requests) {
+        let guard = match access {
+            Access::Read => TicketGuard::Read(handle.read()?),
+            Access::Write => TicketGuard::Write(handle.write()?),
+        };
+        guards.insert(id, guard);
+    }
+    Ok(MultiGuard { guards, published })
+}
+
+/// Lock every ticket in `requests`, giving up after `timeout`.
+///
+/// If some of the locks are taken, the ones acquired so far are released
+/// before trying again, so that other threads can make progress in the meantime.
+pub(crate) fn try_lock_all<'a>(
+    requests: Vec<LockRequest<'a>>,
+    timeout: Duration,
+    published: &'a Published,
+) -> Result<MultiGuard<'a>, LockError> {
+    let deadline = Instant::now() + timeout;
+    let requests = in_lock_order(requests);
+    'attempt: loop {
+        let mut guards = BTreeMap::new();
+        for &(id, handle, access) in &requests {
+            let guard = match access {
+                Access::Read => handle.try_read()?.map(TicketGuard::Read),
+                Access::Write => handle.try_write()?.map(TicketGuard::Write),
+            };
+            match guard {
+                Some(guard) => {
+                    guards.insert(id, guard);
+                }
+                None => {
+                    drop(guards);
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        return Err(LockError::Timeout);
+                    }
+                    sleep(remaining.min(RETRY_INTERVAL));
+                    continue 'attempt;
+                }
+            }
+        }
+        return Ok(MultiGuard { guards, published });
+    }
+}
+
+impl From<HandleError> for LockError {
+    fn from(error: HandleError) -> Self {
+        match error {
+            HandleError::Quarantined(id) => LockError::Quarantined(id),
+            HandleError::InvariantViolated(id) => LockError::InvariantViolated(id),
+        }
+    }
+}
+
+/// Sort `requests` by id, merging the ones targeting the same ticket.
+fn in_lock_order(requests: Vec<LockRequest<'_>>) -> Vec<LockRequest<'_>> {
+    let mut merged: BTreeMap<TicketId, (&TicketHandle, Access)> = BTreeMap::new();
+    for (id, handle, access) in requests {
+        let entry = merged.entry(id).or_insert((handle, access));
+        if access == Access::Write {
+            entry.1 = Access::Write;
+        }
+    }
+    merged
+        .into_iter()
+        .map(|(id, (handle, access))| (id, handle, access))
+        .collect()
+}
@@ -0,0 +1,73 @@
+//! Point-in-time views of a ticket store.
+//!
+//! Besides the live tickets, each store keeps a copy of the last committed version
+//! of every ticket in a persistent map, shared with the snapshots taken from it.
+//! Taking a snapshot only clones the map's root, and a commit only copies the path
+//! to the tickets it changes: neither depends on how many snapshots are alive,
+//! and neither copies the whole map.
+
+use std::sync::{Arc, Mutex, PoisonError};
+
+use imbl::OrdMap;
+
+use crate::data::Ticket;
+use crate::store::TicketId;
+
+type Versions = OrdMap<TicketId, Arc<Ticket>>;
+
+/// An immutable view of the tickets, as they were when the snapshot was taken.
+///
+/// Changes committed afterwards are never visible, however long the snapshot is kept.
+#[derive(Clone, Default)]
+pub struct Snapshot {
+    tickets: Versions,
+}
+
+impl Snapshot {
+    pub fn get(&self, id: TicketId) -> Option<&Ticket> {
+        self.tickets.get(&id).map(|ticket| &**ticket)
+    }
+
+    /// All the tickets, in id order.
+    pub fn iter(&self) -> impl Iterator<Item = &Ticket> {
+        self.tickets.values().map(|ticket| &**ticket)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tickets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tickets.is_empty()
+    }
+}
+
+/// The last committed version of every ticket of a store.
+#[derive(Default)]
+pub(crate) struct Published(Mutex<Versions>);
+
+impl Published {
+    /// Start from the versions in `snapshot`.
+    pub(crate) fn from_snapshot(snapshot: Snapshot) -> Self {
+        Self(Mutex::new(snapshot.tickets))
+    }
+
+    /// Commit new versions of `tickets`, all at once.
+    pub(crate) fn publish(&self, tickets: impl IntoIterator<Item = Ticket>) {
+        // Build the new versions before taking the lock, to keep it short.
+        let tickets: Vec<_> = tickets
+            .into_iter()
+            .map(|ticket| (ticket.id, Arc::new(ticket)))
+            .collect();
+        // Nothing in here panics midway, so a poisoned lock still guards a consistent map.
+        let mut versions = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        versions.extend(tickets);
+    }
+
+    pub(crate) fn snapshot(&self) -> Snapshot {
+        let versions = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        Snapshot {
+            tickets: versions.clone(),
+        }
+    }
+}
@@ -1,22 +1,54 @@
 use std::collections::BTreeMap;
-use std::sync::{Arc, RwLock};
+use std::sync::Arc;
+use std::time::Duration;
 
 use crate::data::{Status, Ticket, TicketDraft};
+use crate::handle::{PoisonPolicy, TicketHandle};
+use crate::multi_lock::{self, Access, LockError, LockRequest, MultiGuard};
+use crate::snapshot::{Published, Snapshot};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
-pub struct TicketId(u64);
+pub struct TicketId(pub(crate) u64);
 
-#[derive(Clone)]
+#[derive(Default)]
 pub struct TicketStore {
-    tickets: BTreeMap<TicketId, Arc<RwLock<Ticket>>>,
+    tickets: BTreeMap<TicketId, TicketHandle>,
     counter: u64,
+    poison_policy: PoisonPolicy,
+    published: Arc<Published>,
+}
+
+impl Clone for TicketStore {
+    /// An independent copy of the store: changes made to either one, tickets included,
+    /// don't show in the other one or in its snapshots.
+    fn clone(&self) -> Self {
+        let published = Arc::new(Published::from_snapshot(self.published.snapshot()));
+        let tickets = self
+            .tickets
+            .iter()
+            .map(|(&id, ticket)| (id, ticket.duplicate(Some(published.clone()))))
+            .collect();
+        Self {
+            tickets,
+            counter: self.counter,
+            poison_policy: self.poison_policy,
+            published,
+        }
+    }
 }
 
 impl TicketStore {
     pub fn new() -> Self {
+        Self::with_poison_policy(PoisonPolicy::default())
+    }
+
+    /// A store whose tickets follow `policy` when their lock gets poisoned.
+    pub fn with_poison_policy(policy: PoisonPolicy) -> Self {
         Self {
             tickets: BTreeMap::new(),
             counter: 0,
+            poison_policy: policy,
+            published: Arc::default(),
         }
     }
 
@@ -29,12 +61,59 @@ impl TicketStore {
             description: ticket.description,
             status: Status::ToDo,
         };
-        let ticket = Arc::new(RwLock::new(ticket));
+        self.published.publish([ticket.clone()]);
+        let ticket = TicketHandle::new(ticket, self.poison_policy, Some(self.published.clone()));
         self.tickets.insert(id, ticket);
         id
     }
 
-    pub fn get(&self, id: TicketId) -> Option<Arc<RwLock<Ticket>>> {
+    pub fn get(&self, id: TicketId) -> Option<TicketHandle> {
         self.tickets.get(&id).cloned()
     }
+
+    /// A point-in-time view of all the tickets, unaffected by later changes.
+    ///
+    /// Changes made through [`TicketStore::lock_many`] are only visible once the
+    /// guard is dropped, all at once.
+    pub fn snapshot(&self) -> Snapshot {
+        self.published.snapshot()
+    }
+
+    /// Lock all the tickets in `ids` for writing.
+    ///
+    /// Locks are acquired in id order, so that two threads locking overlapping
+    /// sets of tickets can't deadlock.
+    pub fn lock_many(&self, ids: &[TicketId]) -> Result<MultiGuard<'_>, LockError> {
+        let requests: Vec<_> = ids.iter().map(|&id| (id, Access::Write)).collect();
+        self.lock_many_with(&requests)
+    }
+
+    /// Lock all the tickets in `requests`, each for reading or for writing.
+    /// See [`TicketStore::lock_many`].
+    pub fn lock_many_with(
+        &self,
+        requests: &[(TicketId, Access)],
+    ) -> Result<MultiGuard<'_>, LockError> {
+        multi_lock::lock_all(self.resolve(requests)?, &self.published)
+    }
+
+    /// Like [`TicketStore::lock_many_with`], but gives up with [`LockError::Timeout`]
+    /// if the tickets couldn't all be locked within `timeout`.
+    pub fn try_lock_many(
+        &self,
+        requests: &[(TicketId, Access)],
+        timeout: Duration,
+    ) -> Result<MultiGuard<'_>, LockError> {
+        multi_lock::try_lock_all(self.resolve(requests)?, timeout, &self.published)
+    }
+
+    fn resolve(&self, requests: &[(TicketId, Access)]) -> Result<Vec<LockRequest<'_>>, LockError> {
+        requests
+            .iter()
+            .map(|&(id, access)| {
+                let ticket = self.tickets.get(&id).ok_or(LockError::NotFound(id))?;
+                Ok((id, ticket, access))
+            })
+            .collect()
+    }
 }
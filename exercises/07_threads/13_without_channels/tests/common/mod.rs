@@ -0,0 +1,9 @@
+use ticket_fields::test_helpers::{ticket_description, ticket_title};
+use without_channels::data::TicketDraft;
+
+pub fn draft() -> TicketDraft {
+    TicketDraft {
+        title: ticket_title(),
+        description: ticket_description(),
+    }
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::thread::spawn;
+
+use common::draft;
+use without_channels::concurrent_store::ConcurrentTicketStore;
+use without_channels::data::Status;
+
+#[test]
+fn concurrent_writers() {
+    let store = Arc::new(ConcurrentTicketStore::with_shards(4));
+
+    let writers: Vec<_> = (0..8)
+        .map(|_| {
+            let store = store.clone();
+            spawn(move || {
+                (0..100)
+                    .map(|_| store.add_ticket(draft()))
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+    let ids: Vec<_> = writers
+        .into_iter()
+        .flat_map(|writer| writer.join().unwrap())
+        .collect();
+
+    // No two writers got the same id.
+    assert_eq!(ids.iter().collect::<BTreeSet<_>>().len(), 800);
+
+    for id in ids {
+        let ticket = store.get(id).unwrap();
+        let ticket = ticket.read().unwrap();
+        assert_eq!(ticket.id, id);
+        assert_eq!(ticket.status, Status::ToDo);
+    }
+}
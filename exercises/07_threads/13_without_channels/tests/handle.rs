@@ -0,0 +1,116 @@
+mod common;
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use common::draft;
+use without_channels::data::Status;
+use without_channels::handle::{HandleError, PoisonPolicy};
+use without_channels::store::TicketStore;
+
+#[test]
+fn updates_are_applied() {
+    let mut store = TicketStore::new();
+    let id = store.add_ticket(draft());
+    let ticket = store.get(id).unwrap();
+
+    let previous = ticket
+        .update(|t| std::mem::replace(&mut t.status, Status::InProgress))
+        .unwrap();
+    assert_eq!(previous, Status::ToDo);
+    assert_eq!(ticket.read().unwrap().status, Status::InProgress);
+}
+
+#[test]
+fn inconsistent_updates_are_rejected() {
+    let mut store = TicketStore::new();
+    let id = store.add_ticket(draft());
+    let other = store.add_ticket(draft());
+    let ticket = store.get(id).unwrap();
+
+    let outcome = ticket.update(|t| {
+        t.status = Status::Done;
+        t.id = other;
+    });
+    assert_eq!(outcome, Err(HandleError::InvariantViolated(id)));
+    assert_eq!(ticket.read().unwrap().status, Status::ToDo);
+}
+
+#[test]
+fn panicking_updates_leave_the_ticket_untouched() {
+    let mut store = TicketStore::new();
+    let id = store.add_ticket(draft());
+    let ticket = store.get(id).unwrap();
+
+    let panicked = catch_unwind(AssertUnwindSafe(|| {
+        ticket.update(|t| {
+            t.status = Status::Done;
+            panic!("Boom");
+        })
+    }));
+    assert!(panicked.is_err());
+
+    assert!(!ticket.is_quarantined());
+    assert_eq!(ticket.read().unwrap().status, Status::ToDo);
+}
+
+#[test]
+fn tickets_left_consistent_by_a_panic_are_recovered() {
+    let mut store = TicketStore::new();
+    let id = store.add_ticket(draft());
+
+    let panicked = catch_unwind(AssertUnwindSafe(|| {
+        let mut guard = store.lock_many(&[id]).unwrap();
+        guard.get_mut(id).unwrap().set_status(Status::Done);
+        panic!("Boom");
+    }));
+    assert!(panicked.is_err());
+
+    let ticket = store.get(id).unwrap();
+    assert!(!ticket.is_quarantined());
+    assert_eq!(ticket.read().unwrap().status, Status::Done);
+}
+
+#[test]
+fn quarantined_tickets_can_be_restored() {
+    let mut store = TicketStore::with_poison_policy(PoisonPolicy::Quarantine);
+    let id = store.add_ticket(draft());
+    let other = store.add_ticket(draft());
+
+    let panicked = catch_unwind(AssertUnwindSafe(|| {
+        let _guard = store.lock_many(&[id]).unwrap();
+        panic!("Boom");
+    }));
+    assert!(panicked.is_err());
+
+    let ticket = store.get(id).unwrap();
+    assert_eq!(ticket.read().err(), Some(HandleError::Quarantined(id)));
+    assert!(ticket.is_quarantined());
+
+    // A copy of another ticket doesn't belong here.
+    let mut restored = store.get(other).unwrap().read().unwrap().clone();
+    assert_eq!(
+        ticket.restore(restored.clone()),
+        Err(HandleError::InvariantViolated(id))
+    );
+    assert!(ticket.is_quarantined());
+
+    restored.id = id;
+    ticket.restore(restored).unwrap();
+    assert!(!ticket.is_quarantined());
+    assert_eq!(ticket.read().unwrap().id, id);
+}
+
+#[test]
+fn the_quarantine_policy_never_recovers() {
+    let mut store = TicketStore::with_poison_policy(PoisonPolicy::Quarantine);
+    let id = store.add_ticket(draft());
+    let ticket = store.get(id).unwrap();
+
+    let panicked = catch_unwind(AssertUnwindSafe(|| ticket.update(|_| panic!("Boom"))));
+    assert!(panicked.is_err());
+
+    assert_eq!(
+        ticket.update(|t| t.status = Status::Done),
+        Err(HandleError::Quarantined(id))
+    );
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use std::sync::Arc;
+use std::thread::spawn;
+use std::time::Duration;
+
+use common::draft;
+use without_channels::data::Status;
+use without_channels::multi_lock::{Access, LockError};
+use without_channels::store::TicketStore;
+
+#[test]
+fn opposite_lock_orders_do_not_deadlock() {
+    let mut store = TicketStore::new();
+    let first = store.add_ticket(draft());
+    let second = store.add_ticket(draft());
+    let store = Arc::new(store);
+
+    let threads: Vec<_> = [[first, second], [second, first]]
+        .into_iter()
+        .map(|ids| {
+            let store = store.clone();
+            spawn(move || {
+                for _ in 0..1_000 {
+                    let mut guard = store.lock_many(&ids).unwrap();
+                    guard
+                        .get_mut(ids[0])
+                        .unwrap()
+                        .set_status(Status::InProgress);
+                    guard.get_mut(ids[1]).unwrap().set_status(Status::Done);
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
+
+#[test]
+fn read_locked_tickets_cannot_be_modified() {
+    let mut store = TicketStore::new();
+    let read = store.add_ticket(draft());
+    let written = store.add_ticket(draft());
+
+    let mut guard = store
+        .lock_many_with(&[(written, Access::Write), (read, Access::Read)])
+        .unwrap();
+    assert!(guard.get_mut(read).is_none());
+    assert_eq!(guard.get(read).unwrap().id, read);
+
+    guard.get_mut(written).unwrap().set_status(Status::Done);
+    drop(guard);
+    assert_eq!(
+        store.get(written).unwrap().read().unwrap().status,
+        Status::Done
+    );
+}
+
+#[test]
+fn try_lock_many_gives_up_after_the_timeout() {
+    let mut store = TicketStore::new();
+    let first = store.add_ticket(draft());
+    let second = store.add_ticket(draft());
+
+    let held = store.lock_many(&[second]).unwrap();
+    let outcome = store.try_lock_many(
+        &[(first, Access::Write), (second, Access::Read)],
+        Duration::from_millis(20),
+    );
+    assert_eq!(outcome.err(), Some(LockError::Timeout));
+
+    // Nothing stays locked after giving up.
+    drop(held);
+    assert!(store
+        .try_lock_many(&[(first, Access::Write)], Duration::ZERO)
+        .is_ok());
+}
+
+#[test]
+fn missing_tickets_are_reported() {
+    let mut store = TicketStore::new();
+    let id = store.add_ticket(draft());
+    // Ids are only handed out by the store, so borrow one from a larger store.
+    let mut other = TicketStore::new();
+    other.add_ticket(draft());
+    let missing = other.add_ticket(draft());
+    let outcome = store.lock_many(&[id, missing]);
+    assert_eq!(outcome.err(), Some(LockError::NotFound(missing)));
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use std::sync::{Arc, RwLock};
+use std::thread::spawn;
+
+use common::draft;
+use without_channels::data::Status;
+use without_channels::store::TicketStore;
+
+#[test]
+fn snapshots_ignore_later_changes() {
+    let mut store = TicketStore::new();
+    let id = store.add_ticket(draft());
+
+    let snapshot = store.snapshot();
+    store
+        .get(id)
+        .unwrap()
+        .update(|t| t.status = Status::Done)
+        .unwrap();
+    store.add_ticket(draft());
+
+    assert_eq!(snapshot.len(), 1);
+    assert_eq!(snapshot.get(id).unwrap().status, Status::ToDo);
+
+    let latest = store.snapshot();
+    assert_eq!(latest.len(), 2);
+    assert_eq!(latest.get(id).unwrap().status, Status::Done);
+}
+
+#[test]
+fn multi_ticket_changes_are_seen_all_at_once() {
+    let mut store = TicketStore::new();
+    let first = store.add_ticket(draft());
+    let second = store.add_ticket(draft());
+    store
+        .get(first)
+        .unwrap()
+        .update(|t| t.status = Status::InProgress)
+        .unwrap();
+    let store = Arc::new(RwLock::new(store));
+
+    // Exactly one of the two tickets is in progress at any time.
+    let writer = {
+        let store = store.clone();
+        spawn(move || {
+            for _ in 0..1_000 {
+                let store = store.read().unwrap();
+                let mut guard = store.lock_many(&[first, second]).unwrap();
+                for id in [first, second] {
+                    let mut ticket = guard.get_mut(id).unwrap();
+                    ticket.set_status(match ticket.status {
+                        Status::InProgress => Status::ToDo,
+                        _ => Status::InProgress,
+                    });
+                }
+            }
+        })
+    };
+
+    for _ in 0..1_000 {
+        let snapshot = store.read().unwrap().snapshot();
+        let in_progress = snapshot
+            .iter()
+            .filter(|ticket| ticket.status == Status::InProgress)
+            .count();
+        assert_eq!(in_progress, 1);
+    }
+    writer.join().unwrap();
+}
+
+#[test]
+fn every_snapshot_keeps_its_own_versions() {
+    let mut store = TicketStore::new();
+    let ids: Vec<_> = (0..100).map(|_| store.add_ticket(draft())).collect();
+
+    let mut snapshots = vec![store.snapshot()];
+    for &id in &ids {
+        store
+            .get(id)
+            .unwrap()
+            .update(|t| t.status = Status::Done)
+            .unwrap();
+        snapshots.push(store.snapshot());
+    }
+
+    // The n-th snapshot was taken after the first n tickets were closed.
+    for (closed, snapshot) in snapshots.iter().enumerate() {
+        assert_eq!(snapshot.len(), ids.len());
+        let done = snapshot
+            .iter()
+            .take_while(|ticket| ticket.status == Status::Done)
+            .count();
+        assert_eq!(done, closed);
+        assert!(snapshot.iter().skip(done).all(|t| t.status == Status::ToDo));
+    }
+}
+
+#[test]
+fn cloned_stores_are_independent() {
+    let mut store = TicketStore::new();
+    let id = store.add_ticket(draft());
+    let mut copy = store.clone();
+
+    copy.get(id)
+        .unwrap()
+        .update(|t| t.status = Status::Done)
+        .unwrap();
+    let added = copy.add_ticket(draft());
+
+    assert_eq!(store.get(id).unwrap().read().unwrap().status, Status::ToDo);
+    assert!(store.get(added).is_none());
+    assert_eq!(store.snapshot().len(), 1);
+    assert_eq!(store.snapshot().get(id).unwrap().status, Status::ToDo);
+    assert_eq!(copy.snapshot().len(), 2);
+    assert_eq!(copy.snapshot().get(id).unwrap().status, Status::Done);
+}
@@ -0,0 +1,56 @@
+//! A record of every change made to the store, and who made it.
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::project::ProjectKey;
+use crate::store::TicketId;
+use crate::users::{Action, User};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub at: SystemTime,
+    /// The name of the user who performed the action.
+    pub user: String,
+    pub action: Action,
+    pub subject: Subject,
+}
+
+/// What an action was performed on.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Subject {
+    Ticket(TicketId),
+    Project(ProjectKey),
+}
+
+impl From<TicketId> for Subject {
+    fn from(id: TicketId) -> Self {
+        Subject::Ticket(id)
+    }
+}
+
+impl From<ProjectKey> for Subject {
+    fn from(key: ProjectKey) -> Self {
+        Subject::Project(key)
+    }
+}
+
+/// Entries are only ever appended, oldest first.
+#[derive(Default)]
+pub(crate) struct AuditLog(Vec<AuditEntry>);
+
+impl AuditLog {
+    pub(crate) fn record(&mut self, user: &User, action: Action, subject: impl Into<Subject>) {
+        self.0.push(AuditEntry {
+            at: SystemTime::now(),
+            user: user.name.clone(),
+            action,
+            subject: subject.into(),
+        });
+    }
+
+    pub(crate) fn entries(&self) -> &[AuditEntry] {
+        &self.0
+    }
+}
@@ -0,0 +1,147 @@
+//! Finding out who is on the other end of a connection.
+//!
+//! The store itself trusts the [`User`] it's given: authentication happens at the edges,
+//! e.g. in [`serve`](crate::remote::serve), through an [`Authenticator`].
+use std::collections::HashMap;
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{self, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+
+use crate::users::{Role, User};
+
+pub trait Authenticator: Send + Sync {
+    /// The user with the given credentials.
+    ///
+    /// Checking passwords is slow by design: call this from async code
+    /// through `tokio::task::spawn_blocking`.
+    fn authenticate(&self, username: &str, password: &str) -> Result<User, AuthError>;
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    /// Unknown users get the same error as wrong passwords,
+    /// so that the error doesn't tell which usernames exist.
+    #[error("Invalid username or password")]
+    InvalidCredentials,
+    #[error("There is already a user named {0}")]
+    UserExists(String),
+    #[error("Could not hash the password: {0}")]
+    Hashing(password_hash::Error),
+}
+
+/// Users kept in memory, with argon2 hashes of their passwords.
+pub struct LocalAuthenticator {
+    argon2: Argon2<'static>,
+    accounts: HashMap<String, Account>,
+    /// Checked against the password of unknown users, so that they take as long
+    /// to reject as wrong passwords: timings don't tell which usernames exist either.
+    dummy_hash: String,
+}
+
+struct Account {
+    /// In PHC string format, salt included.
+    password_hash: String,
+    role: Role,
+}
+
+impl LocalAuthenticator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hash `password` with a fresh salt, in PHC string format.
+    fn hash(&self, password: &str) -> Result<String, AuthError> {
+        let salt = SaltString::generate(&mut OsRng);
+        Ok(self
+            .argon2
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(AuthError::Hashing)?
+            .to_string())
+    }
+
+    pub fn add_user(
+        &mut self,
+        name: impl Into<String>,
+        password: &str,
+        role: Role,
+    ) -> Result<User, AuthError> {
+        let name = name.into();
+        if self.accounts.contains_key(&name) {
+            return Err(AuthError::UserExists(name));
+        }
+        let password_hash = self.hash(password)?;
+        self.accounts.insert(
+            name.clone(),
+            Account {
+                password_hash,
+                role,
+            },
+        );
+        Ok(User::new(name, role))
+    }
+}
+
+impl Default for LocalAuthenticator {
+    fn default() -> Self {
+        let mut authenticator = Self {
+            argon2: Argon2::default(),
+            accounts: HashMap::new(),
+            dummy_hash: String::new(),
+        };
+        // Nobody knows this password: it's only ever compared against wrong ones.
+        let password = SaltString::generate(&mut OsRng);
+        authenticator.dummy_hash = authenticator
+            .hash(password.as_str())
+            .expect("The default argon2 parameters can hash any password");
+        authenticator
+    }
+}
+
+impl Authenticator for LocalAuthenticator {
+    fn authenticate(&self, username: &str, password: &str) -> Result<User, AuthError> {
+        let account = self.accounts.get(username);
+        let password_hash = account.map_or(&self.dummy_hash, |account| &account.password_hash);
+        let password_hash = PasswordHash::new(password_hash).map_err(AuthError::Hashing)?;
+        let verified = self
+            .argon2
+            .verify_password(password.as_bytes(), &password_hash);
+        match (account, verified) {
+            (Some(account), Ok(())) => Ok(User::new(username, account.role)),
+            _ => Err(AuthError::InvalidCredentials),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passwords_are_checked() {
+        let mut authenticator = LocalAuthenticator::new();
+        let alice = authenticator
+            .add_user("alice", "correct horse", Role::Developer)
+            .unwrap();
+        assert_eq!(alice, User::new("alice", Role::Developer));
+
+        assert_eq!(
+            authenticator
+                .authenticate("alice", "correct horse")
+                .unwrap(),
+            alice
+        );
+        assert!(matches!(
+            authenticator.authenticate("alice", "battery staple"),
+            Err(AuthError::InvalidCredentials)
+        ));
+        assert!(matches!(
+            authenticator.authenticate("bob", "correct horse"),
+            Err(AuthError::InvalidCredentials)
+        ));
+        assert!(matches!(
+            authenticator.add_user("alice", "again", Role::Admin),
+            Err(AuthError::UserExists(_))
+        ));
+    }
+}
@@ -0,0 +1,410 @@
+//! Extra fields whose presence and type depend on the type of the ticket.
+//!
+//! Each [`Project`](crate::project::Project) declares the [`TicketType`]s it supports
+//! (bug, story, incident...), and each type declares its [`FieldDefinition`]s.
+//! Values are checked against them whenever a ticket is created or patched.
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TicketType {
+    pub name: String,
+    pub fields: Vec<FieldDefinition>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldDefinition {
+    pub name: String,
+    pub kind: FieldKind,
+    /// Whether every ticket of the type must have a value for the field.
+    #[serde(default)]
+    pub required: bool,
+}
+
+/// What a field holds, and the rules its values must follow.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FieldKind {
+    String {
+        /// In characters.
+        max_length: Option<usize>,
+    },
+    /// A whole number, e.g. story points.
+    Number {
+        min: Option<i64>,
+        max: Option<i64>,
+    },
+    /// One of a fixed list of values, e.g. a severity.
+    Enum {
+        options: Vec<String>,
+    },
+    Date,
+    /// The name of a user. Whether the user exists isn't checked:
+    /// users are known to the authenticator, not to the store.
+    User,
+}
+
+/// A custom field value.
+///
+/// In JSON, numbers are numbers and everything else is a string, dates being formatted
+/// as `YYYY-MM-DD`. Strings are turned into the right variant for their field when
+/// they're checked.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FieldValue {
+    Number(i64),
+    Date(NaiveDate),
+    Text(String),
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Number(n) => write!(f, "{n}"),
+            FieldValue::Date(date) => write!(f, "{}", date.format("%Y-%m-%d")),
+            FieldValue::Text(text) => f.write_str(text),
+        }
+    }
+}
+
+impl From<&str> for FieldValue {
+    fn from(text: &str) -> Self {
+        FieldValue::Text(text.to_string())
+    }
+}
+
+impl From<i64> for FieldValue {
+    fn from(n: i64) -> Self {
+        FieldValue::Number(n)
+    }
+}
+
+impl From<NaiveDate> for FieldValue {
+    fn from(date: NaiveDate) -> Self {
+        FieldValue::Date(date)
+    }
+}
+
+/// Custom field values, keyed by field name.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CustomFields(BTreeMap<String, FieldValue>);
+
+impl CustomFields {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a value for `field`, replacing the previous one.
+    pub fn with(mut self, field: impl Into<String>, value: impl Into<FieldValue>) -> Self {
+        self.0.insert(field.into(), value.into());
+        self
+    }
+
+    pub fn get(&self, field: &str) -> Option<&FieldValue> {
+        self.0.get(field)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &FieldValue)> {
+        self.0.iter().map(|(field, value)| (field.as_str(), value))
+    }
+
+    /// Whether every value in `filter` is also in `self`.
+    pub fn matches(&self, filter: &CustomFields) -> bool {
+        filter.iter().all(|(field, expected)| {
+            // Filters may not have been checked yet: compare them as they'd be written.
+            self.get(field)
+                .is_some_and(|value| value.to_string() == expected.to_string())
+        })
+    }
+
+    /// `self`, with `changes` applied.
+    pub(crate) fn merged(&self, changes: CustomFieldChanges) -> Self {
+        let mut merged = self.clone();
+        for (field, value) in changes.0 {
+            match value {
+                Some(value) => merged.0.insert(field, value),
+                None => merged.0.remove(&field),
+            };
+        }
+        merged
+    }
+}
+
+/// Changes to custom field values, keyed by field name.
+///
+/// A value adds or replaces the value of its field, `None` (`null` in JSON) removes it.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CustomFieldChanges(BTreeMap<String, Option<FieldValue>>);
+
+impl CustomFieldChanges {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set `field` to `value`.
+    pub fn with(mut self, field: impl Into<String>, value: impl Into<FieldValue>) -> Self {
+        self.0.insert(field.into(), Some(value.into()));
+        self
+    }
+
+    /// Remove the value of `field`.
+    pub fn without(mut self, field: impl Into<String>) -> Self {
+        self.0.insert(field.into(), None);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error, Serialize, Deserialize)]
+pub enum CustomFieldError {
+    #[error("There is no ticket type named {0}")]
+    UnknownType(String),
+    #[error("There is no custom field named {0}")]
+    UnknownField(String),
+    #[error("The {0} field is required")]
+    Missing(String),
+    #[error("The {field} field must be {expected}")]
+    WrongType { field: String, expected: String },
+    #[error("The {0} field cannot be empty")]
+    Empty(String),
+    #[error("The {field} field cannot be longer than {max} characters")]
+    TooLong { field: String, max: usize },
+    #[error("The {field} field cannot be less than {min}")]
+    TooSmall { field: String, min: i64 },
+    #[error("The {field} field cannot be more than {max}")]
+    TooLarge { field: String, max: i64 },
+    #[error("The {field} field must be one of: {}", options.join(", "))]
+    NotAnOption { field: String, options: Vec<String> },
+}
+
+impl TicketType {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn with_field(mut self, name: impl Into<String>, kind: FieldKind, required: bool) -> Self {
+        self.fields.push(FieldDefinition {
+            name: name.into(),
+            kind,
+            required,
+        });
+        self
+    }
+
+    /// Check `fields` against the type's definitions, converting each value
+    /// to the variant of its field.
+    pub fn check(&self, fields: CustomFields) -> Result<CustomFields, CustomFieldError> {
+        let mut checked = CustomFields::new();
+        for (name, value) in fields.0 {
+            let definition = self
+                .fields
+                .iter()
+                .find(|definition| definition.name == name)
+                .ok_or_else(|| CustomFieldError::UnknownField(name.clone()))?;
+            let value = definition.check(value)?;
+            checked.0.insert(name, value);
+        }
+        if let Some(missing) = self
+            .fields
+            .iter()
+            .find(|definition| definition.required && checked.get(&definition.name).is_none())
+        {
+            return Err(CustomFieldError::Missing(missing.name.clone()));
+        }
+        Ok(checked)
+    }
+}
+
+impl FieldDefinition {
+    fn check(&self, value: FieldValue) -> Result<FieldValue, CustomFieldError> {
+        let field = || self.name.clone();
+        let wrong_type = |expected: &str| CustomFieldError::WrongType {
+            field: field(),
+            expected: expected.to_string(),
+        };
+        // Dates are text too, if the field isn't a date.
+        let text = match &value {
+            FieldValue::Number(_) => None,
+            FieldValue::Date(_) | FieldValue::Text(_) => Some(value.to_string()),
+        };
+        match &self.kind {
+            FieldKind::String { max_length } => {
+                let text = text.ok_or_else(|| wrong_type("text"))?;
+                if text.is_empty() {
+                    return Err(CustomFieldError::Empty(field()));
+                }
+                if let Some(max) = *max_length {
+                    if text.chars().count() > max {
+                        return Err(CustomFieldError::TooLong {
+                            field: field(),
+                            max,
+                        });
+                    }
+                }
+                Ok(FieldValue::Text(text))
+            }
+            FieldKind::Number { min, max } => {
+                let FieldValue::Number(n) = value else {
+                    return Err(wrong_type("a whole number"));
+                };
+                if let Some(min) = *min {
+                    if n < min {
+                        return Err(CustomFieldError::TooSmall {
+                            field: field(),
+                            min,
+                        });
+                    }
+                }
+                if let Some(max) = *max {
+                    if n > max {
+                        return Err(CustomFieldError::TooLarge {
+                            field: field(),
+                            max,
+                        });
+                    }
+                }
+                Ok(value)
+            }
+            FieldKind::Enum { options } => {
+                let text = text.ok_or_else(|| wrong_type("text"))?;
+                if !options.contains(&text) {
+                    return Err(CustomFieldError::NotAnOption {
+                        field: field(),
+                        options: options.clone(),
+                    });
+                }
+                Ok(FieldValue::Text(text))
+            }
+            FieldKind::Date => match value {
+                FieldValue::Date(_) => Ok(value),
+                FieldValue::Text(text) => NaiveDate::parse_from_str(&text, "%Y-%m-%d")
+                    .map(FieldValue::Date)
+                    .map_err(|_| wrong_type("a date, formatted as YYYY-MM-DD")),
+                FieldValue::Number(_) => Err(wrong_type("a date, formatted as YYYY-MM-DD")),
+            },
+            FieldKind::User => {
+                let text = text.ok_or_else(|| wrong_type("a user name"))?;
+                if text.is_empty() {
+                    return Err(CustomFieldError::Empty(field()));
+                }
+                Ok(FieldValue::Text(text))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bug() -> TicketType {
+        TicketType::new("bug")
+            .with_field(
+                "severity",
+                FieldKind::Enum {
+                    options: vec!["low".into(), "high".into()],
+                },
+                true,
+            )
+            .with_field(
+                "points",
+                FieldKind::Number {
+                    min: Some(0),
+                    max: Some(13),
+                },
+                false,
+            )
+            .with_field("due", FieldKind::Date, false)
+            .with_field(
+                "version",
+                FieldKind::String {
+                    max_length: Some(5),
+                },
+                false,
+            )
+            .with_field("owner", FieldKind::User, false)
+    }
+
+    #[test]
+    fn values_are_converted_to_their_field_type() {
+        let fields: CustomFields = serde_json::from_str(
+            r#"{"severity": "high", "points": 3, "due": "2024-05-01", "version": "1.2"}"#,
+        )
+        .unwrap();
+        let checked = bug().check(fields).unwrap();
+        assert_eq!(
+            checked.get("due"),
+            Some(&FieldValue::Date(
+                NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()
+            ))
+        );
+        assert_eq!(checked.get("points"), Some(&FieldValue::Number(3)));
+        assert_eq!(serde_json::to_value(&checked).unwrap()["due"], "2024-05-01");
+    }
+
+    #[test]
+    fn invalid_values_are_rejected() {
+        let check = |fields: CustomFields| bug().check(fields).unwrap_err().to_string();
+        let valid = || CustomFields::new().with("severity", "low");
+
+        assert_eq!(check(CustomFields::new()), "The severity field is required");
+        assert_eq!(
+            check(valid().with("severity", "meh")),
+            "The severity field must be one of: low, high"
+        );
+        assert_eq!(
+            check(valid().with("points", 21)),
+            "The points field cannot be more than 13"
+        );
+        assert_eq!(
+            check(valid().with("points", "three")),
+            "The points field must be a whole number"
+        );
+        assert_eq!(
+            check(valid().with("due", "tomorrow")),
+            "The due field must be a date, formatted as YYYY-MM-DD"
+        );
+        assert_eq!(
+            check(valid().with("version", "1.2.3.4")),
+            "The version field cannot be longer than 5 characters"
+        );
+        assert_eq!(
+            check(valid().with("owner", "")),
+            "The owner field cannot be empty"
+        );
+        assert_eq!(
+            check(valid().with("color", "red")),
+            "There is no custom field named color"
+        );
+    }
+
+    #[test]
+    fn filters_match_on_the_written_form() {
+        let fields = bug()
+            .check(
+                CustomFields::new()
+                    .with("severity", "high")
+                    .with("due", "2024-05-01"),
+            )
+            .unwrap();
+        assert!(fields.matches(&CustomFields::new().with("due", "2024-05-01")));
+        assert!(fields.matches(&CustomFields::new()));
+        assert!(!fields.matches(&CustomFields::new().with("severity", "low")));
+        assert!(!fields.matches(&CustomFields::new().with("points", 1)));
+    }
+}
@@ -0,0 +1,107 @@
+use crate::custom_fields::{CustomFieldChanges, CustomFields};
+use crate::project::{ProjectKey, TicketKey};
+use crate::store::TicketId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use ticket_fields::{TicketDescription, TicketTitle};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Ticket {
+    pub id: TicketId,
+    pub key: TicketKey,
+    pub title: TicketTitle,
+    pub description: TicketDescription,
+    pub status: Status,
+    /// The name of the user who created the ticket.
+    pub reporter: String,
+    /// The name of the user working on the ticket, if any.
+    pub assignee: Option<String>,
+    /// One of the ticket types of the project, if any.
+    pub ticket_type: Option<String>,
+    /// Checked against the definitions of `ticket_type`.
+    pub custom_fields: CustomFields,
+    pub created_at: DateTime<Utc>,
+    /// When the ticket was last changed.
+    pub updated_at: DateTime<Utc>,
+    /// When the ticket was moved to [`Status::Done`], unless it's been reopened since.
+    pub closed_at: Option<DateTime<Utc>>,
+    /// Every status the ticket went through, oldest first, starting with its creation.
+    pub history: Vec<Transition>,
+}
+
+impl Ticket {
+    /// The status the ticket had at `at`, or `None` if it didn't exist yet.
+    pub fn status_at(&self, at: DateTime<Utc>) -> Option<Status> {
+        self.history
+            .iter()
+            .take_while(|transition| transition.at <= at)
+            .last()
+            .map(|transition| transition.status)
+    }
+}
+
+/// A change of status, or the creation of a ticket.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transition {
+    pub status: Status,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TicketDraft {
+    /// The project the ticket belongs to. It must exist in the store.
+    pub project: ProjectKey,
+    pub title: TicketTitle,
+    pub description: TicketDescription,
+    /// One of the ticket types of the project. Tickets without a type
+    /// can't have custom fields.
+    #[serde(default)]
+    pub ticket_type: Option<String>,
+    #[serde(default)]
+    pub custom_fields: CustomFields,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TicketPatch {
+    pub id: TicketId,
+    pub title: Option<TicketTitle>,
+    pub description: Option<TicketDescription>,
+    pub status: Option<Status>,
+    /// The name of the user to assign the ticket to.
+    pub assignee: Option<String>,
+    /// Values to add, replace or remove. Fields that aren't listed keep their value.
+    #[serde(default)]
+    pub custom_fields: CustomFieldChanges,
+}
+
+#[derive(Clone, Debug, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Status {
+    ToDo,
+    InProgress,
+    Done,
+}
+
+/// Which tickets to list: those matching every field that is set.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TicketFilter {
+    pub project: Option<ProjectKey>,
+    pub status: Option<Status>,
+    pub ticket_type: Option<String>,
+    /// Tickets must have each of these values.
+    #[serde(default)]
+    pub custom_fields: CustomFields,
+}
+
+impl TicketFilter {
+    pub fn matches(&self, ticket: &Ticket) -> bool {
+        self.project
+            .as_ref()
+            .is_none_or(|project| ticket.key.project == *project)
+            && self.status.is_none_or(|status| ticket.status == status)
+            && self
+                .ticket_type
+                .as_ref()
+                .is_none_or(|ticket_type| ticket.ticket_type.as_ref() == Some(ticket_type))
+            && ticket.custom_fields.matches(&self.custom_fields)
+    }
+}
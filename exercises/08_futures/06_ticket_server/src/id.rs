@@ -0,0 +1,297 @@
+//! Ticket ids, and the ways a [`TicketStore`](crate::store::TicketStore) can generate them.
+//!
+//! Ids are printed and parsed in the format they were generated in: a number for
+//! sequential and Snowflake ids, 26 characters of Crockford base32 for ULIDs, and the
+//! usual hyphenated form for UUIDs. ULIDs and UUIDs are behind the `ulid` and `uuid` features.
+//!
+//! Every id is sent as a JSON string, numbers included: Snowflake ids go beyond 2^53,
+//! the largest integer a JavaScript client can read from JSON without rounding it.
+//! Ids sent as JSON numbers are still accepted.
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TicketId(Repr);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Repr {
+    Number(u64),
+    #[cfg(feature = "ulid")]
+    Ulid(ulid::Ulid),
+    #[cfg(feature = "uuid")]
+    Uuid(uuid::Uuid),
+}
+
+impl fmt::Display for TicketId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Repr::Number(n) => write!(f, "{n}"),
+            #[cfg(feature = "ulid")]
+            Repr::Ulid(ulid) => write!(f, "{ulid}"),
+            #[cfg(feature = "uuid")]
+            Repr::Uuid(uuid) => write!(f, "{}", uuid.hyphenated()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid ticket id: {0:?}")]
+pub struct ParseTicketIdError(String);
+
+impl FromStr for TicketId {
+    type Err = ParseTicketIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseTicketIdError(s.to_string());
+        if s.bytes().all(|b| b.is_ascii_digit()) {
+            return s
+                .parse()
+                .map(|n| TicketId(Repr::Number(n)))
+                .map_err(|_: ParseIntError| invalid());
+        }
+        #[cfg(feature = "uuid")]
+        if s.len() == 36 {
+            if let Ok(uuid) = uuid::Uuid::try_parse(s) {
+                return Ok(TicketId(Repr::Uuid(uuid)));
+            }
+        }
+        #[cfg(feature = "ulid")]
+        if let Ok(ulid) = ulid::Ulid::from_string(s) {
+            return Ok(TicketId(Repr::Ulid(ulid)));
+        }
+        Err(invalid())
+    }
+}
+
+impl Serialize for TicketId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TicketId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(TicketIdVisitor)
+    }
+}
+
+struct TicketIdVisitor;
+
+impl Visitor<'_> for TicketIdVisitor {
+    type Value = TicketId;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a ticket id, as a number or a string")
+    }
+
+    fn visit_u64<E: de::Error>(self, n: u64) -> Result<TicketId, E> {
+        Ok(TicketId(Repr::Number(n)))
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<TicketId, E> {
+        s.parse().map_err(E::custom)
+    }
+}
+
+/// How a store comes up with the id of each new ticket.
+#[derive(Clone, Debug)]
+pub struct IdGenerator(Generator);
+
+#[derive(Clone, Debug)]
+enum Generator {
+    Sequential {
+        next: u64,
+    },
+    Snowflake(Snowflake),
+    #[cfg(feature = "ulid")]
+    Ulid {
+        previous: Option<ulid::Ulid>,
+    },
+    #[cfg(feature = "uuid")]
+    UuidV7,
+}
+
+impl IdGenerator {
+    /// `0`, `1`, `2`... Short, but only unique within a single store.
+    pub fn sequential() -> Self {
+        Self(Generator::Sequential { next: 0 })
+    }
+
+    /// Time-ordered 64-bit ids, unique across stores as long as each of them
+    /// has its own `node` number.
+    ///
+    /// An id is made of the milliseconds elapsed since [`SNOWFLAKE_EPOCH`] (41 bits),
+    /// the node (10 bits), and a sequence number for ids created in the same millisecond
+    /// (12 bits).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `node` doesn't fit in 10 bits, i.e. it's 1024 or more.
+    pub fn snowflake(node: u16) -> Self {
+        assert!(
+            node < 1 << 10,
+            "Snowflake nodes go from 0 to 1023, got {node}"
+        );
+        Self(Generator::Snowflake(Snowflake {
+            node,
+            last_millis: 0,
+            sequence: 0,
+        }))
+    }
+
+    /// Time-ordered, random 128-bit ids that can be created anywhere without coordination.
+    /// Ids created by the same generator within a millisecond still come in order.
+    #[cfg(feature = "ulid")]
+    pub fn ulid() -> Self {
+        Self(Generator::Ulid { previous: None })
+    }
+
+    /// Like [`ulid`](Self::ulid), in the standard UUID version 7 format.
+    #[cfg(feature = "uuid")]
+    pub fn uuid_v7() -> Self {
+        Self(Generator::UuidV7)
+    }
+
+    pub fn next_id(&mut self) -> TicketId {
+        let repr = match &mut self.0 {
+            Generator::Sequential { next } => {
+                let id = *next;
+                *next += 1;
+                Repr::Number(id)
+            }
+            Generator::Snowflake(snowflake) => Repr::Number(snowflake.next()),
+            #[cfg(feature = "ulid")]
+            Generator::Ulid { previous } => {
+                let new = ulid::Ulid::new();
+                // Overflowing the 80 random bits within a millisecond is as good as impossible.
+                let ulid = match *previous {
+                    Some(previous) if new <= previous => previous.increment().unwrap_or(new),
+                    _ => new,
+                };
+                *previous = Some(ulid);
+                Repr::Ulid(ulid)
+            }
+            #[cfg(feature = "uuid")]
+            Generator::UuidV7 => Repr::Uuid(uuid::Uuid::now_v7()),
+        };
+        TicketId(repr)
+    }
+}
+
+impl Default for IdGenerator {
+    fn default() -> Self {
+        Self::sequential()
+    }
+}
+
+/// Where Snowflake timestamps start: 2024-01-01T00:00:00Z, as a duration since the Unix epoch.
+pub const SNOWFLAKE_EPOCH: Duration = Duration::from_millis(1_704_067_200_000);
+
+#[derive(Clone, Debug)]
+struct Snowflake {
+    node: u16,
+    last_millis: u64,
+    sequence: u16,
+}
+
+impl Snowflake {
+    fn next(&mut self) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH + SNOWFLAKE_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        // If the clock goes backwards, or if we run out of sequence numbers within
+        // a millisecond, we borrow from the future rather than repeat an id.
+        if now > self.last_millis {
+            self.last_millis = now;
+            self.sequence = 0;
+        } else if self.sequence == (1 << 12) - 1 {
+            self.last_millis += 1;
+            self.sequence = 0;
+        } else {
+            self.sequence += 1;
+        }
+        (self.last_millis << 22) | (u64::from(self.node) << 12) | u64::from(self.sequence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(mut generator: IdGenerator, n: usize) -> Vec<TicketId> {
+        (0..n).map(|_| generator.next_id()).collect()
+    }
+
+    /// Ids are unique, and come in order.
+    fn assert_strictly_increasing(ids: &[TicketId]) {
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    /// Ids survive being printed then parsed, and being sent as JSON.
+    fn assert_round_trip(id: TicketId) {
+        assert_eq!(id.to_string().parse::<TicketId>(), Ok(id));
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(serde_json::from_str::<TicketId>(&json).unwrap(), id);
+    }
+
+    #[test]
+    fn sequential_ids_are_numbers() {
+        let ids = ids(IdGenerator::sequential(), 3);
+        assert_eq!(ids[2].to_string(), "2");
+        assert_eq!(serde_json::to_string(&ids[2]).unwrap(), "\"2\"");
+        // As sent before ids became strings.
+        assert_eq!(serde_json::from_str::<TicketId>("2").unwrap(), ids[2]);
+        assert_round_trip(ids[0]);
+    }
+
+    #[test]
+    fn snowflake_ids_are_ordered_even_within_a_millisecond() {
+        // More than the 4096 sequence numbers available in a millisecond.
+        let ids = ids(IdGenerator::snowflake(7), 10_000);
+        assert_strictly_increasing(&ids);
+        assert_round_trip(ids[0]);
+    }
+
+    #[test]
+    fn snowflake_ids_survive_json_readers_limited_to_doubles() {
+        let id = ids(IdGenerator::snowflake(1023), 1)[0];
+        let n: u64 = id.to_string().parse().unwrap();
+        assert!(n > 1 << 53, "Expected a Snowflake id beyond 2^53, got {n}");
+
+        let json = serde_json::to_value(id).unwrap();
+        assert_eq!(json, serde_json::Value::String(n.to_string()));
+        assert_eq!(serde_json::from_value::<TicketId>(json).unwrap(), id);
+    }
+
+    #[test]
+    #[cfg(feature = "ulid")]
+    fn ulids() {
+        let ids = ids(IdGenerator::ulid(), 1_000);
+        assert_strictly_increasing(&ids);
+        assert_eq!(ids[0].to_string().len(), 26);
+        assert_round_trip(ids[0]);
+    }
+
+    #[test]
+    #[cfg(feature = "uuid")]
+    fn uuids() {
+        let ids = ids(IdGenerator::uuid_v7(), 1_000);
+        assert_strictly_increasing(&ids);
+        assert_eq!(ids[0].to_string().len(), 36);
+        assert_round_trip(ids[0]);
+    }
+
+    #[test]
+    fn invalid_ids_are_rejected() {
+        assert!("".parse::<TicketId>().is_err());
+        assert!("-1".parse::<TicketId>().is_err());
+        assert!("99999999999999999999999".parse::<TicketId>().is_err());
+        assert!(serde_json::from_str::<TicketId>("\"nope\"").is_err());
+    }
+}
@@ -0,0 +1,926 @@
+//! The ticket server from `07_threads`, running as a tokio task instead of a thread.
+//!
+//! Requests go through a bounded `tokio::sync::mpsc` channel, and each reply comes back
+//! on its own `oneshot` channel: neither the client nor the server ever blocks a thread.
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+use crate::audit::{AuditEntry, AuditLog};
+use crate::data::{Status, Ticket, TicketDraft, TicketFilter, TicketPatch};
+use crate::project::{Project, TicketKey};
+use crate::store::{StoreError, TicketId, TicketStore};
+use crate::users::{Action, User};
+
+pub mod audit;
+pub mod auth;
+pub mod clock;
+pub mod custom_fields;
+pub mod data;
+pub mod id;
+pub mod project;
+pub mod protocol;
+pub mod remote;
+pub mod store;
+pub mod users;
+
+/// Every call is made on behalf of a [`User`], who must be allowed to perform it.
+/// The client trusts the user it's given: check who they are with an
+/// [`Authenticator`](crate::auth::Authenticator) first.
+#[derive(Clone)]
+pub struct TicketStoreClient {
+    sender: mpsc::Sender<Command>,
+}
+
+impl TicketStoreClient {
+    /// Create a ticket from `draft`, returning it.
+    pub async fn insert(&self, user: &User, draft: TicketDraft) -> Result<Ticket, ClientError> {
+        self.call(|response_channel| Command::Insert {
+            user: user.clone(),
+            draft,
+            response_channel,
+        })
+        .await
+    }
+
+    pub async fn get(&self, user: &User, id: TicketId) -> Result<Ticket, ClientError> {
+        self.call(|response_channel| Command::Get {
+            user: user.clone(),
+            id,
+            response_channel,
+        })
+        .await
+    }
+
+    pub async fn find(&self, user: &User, key: TicketKey) -> Result<Ticket, ClientError> {
+        self.call(|response_channel| Command::Find {
+            user: user.clone(),
+            key,
+            response_channel,
+        })
+        .await
+    }
+
+    /// Apply `patch`, returning the updated ticket.
+    pub async fn update(&self, user: &User, patch: TicketPatch) -> Result<Ticket, ClientError> {
+        self.call(|response_channel| Command::Update {
+            user: user.clone(),
+            patch,
+            response_channel,
+        })
+        .await
+    }
+
+    /// Remove the ticket with the given `id`, returning it.
+    pub async fn remove(&self, user: &User, id: TicketId) -> Result<Ticket, ClientError> {
+        self.call(|response_channel| Command::Remove {
+            user: user.clone(),
+            id,
+            response_channel,
+        })
+        .await
+    }
+
+    /// The tickets matching `filter`, in id order.
+    pub async fn list(
+        &self,
+        user: &User,
+        filter: TicketFilter,
+    ) -> Result<Vec<Ticket>, ClientError> {
+        self.call(|response_channel| Command::List {
+            user: user.clone(),
+            filter,
+            response_channel,
+        })
+        .await
+    }
+
+    pub async fn add_project(&self, user: &User, project: Project) -> Result<(), ClientError> {
+        self.call(|response_channel| Command::AddProject {
+            user: user.clone(),
+            project,
+            response_channel,
+        })
+        .await
+    }
+
+    /// Every project, in key order.
+    pub async fn projects(&self, user: &User) -> Result<Vec<Project>, ClientError> {
+        self.call(|response_channel| Command::Projects {
+            user: user.clone(),
+            response_channel,
+        })
+        .await
+    }
+
+    /// Every change made to the store so far, oldest first.
+    pub async fn audit_log(&self, user: &User) -> Result<Vec<AuditEntry>, ClientError> {
+        self.call(|response_channel| Command::AuditLog {
+            user: user.clone(),
+            response_channel,
+        })
+        .await
+    }
+
+    /// Queue the command built by `command`, then wait for the server's reply.
+    ///
+    /// Queueing never waits: if the queue is full, the request fails
+    /// with [`ClientError::Overloaded`].
+    async fn call<T>(
+        &self,
+        command: impl FnOnce(oneshot::Sender<Result<T, ClientError>>) -> Command,
+    ) -> Result<T, ClientError> {
+        let (response_sender, response_receiver) = oneshot::channel();
+        self.sender
+            .try_send(command(response_sender))
+            .map_err(|e| match e {
+                mpsc::error::TrySendError::Full(_) => ClientError::Overloaded,
+                mpsc::error::TrySendError::Closed(_) => ClientError::Disconnected,
+            })?;
+        // The server drops the response channel without replying
+        // if it stops before getting to our command.
+        response_receiver
+            .await
+            .map_err(|_| ClientError::Disconnected)?
+    }
+}
+
+/// Everything that can go wrong when talking to the server.
+#[derive(Debug, thiserror::Error, Serialize, Deserialize)]
+pub enum ClientError {
+    #[error("The store is overloaded")]
+    Overloaded,
+    /// The server has stopped, either because it was shut down or because it panicked.
+    #[error("The server is no longer running")]
+    Disconnected,
+    #[error("There is no ticket with id {0}")]
+    NotFound(TicketId),
+    #[error("{user} is not allowed to {action}")]
+    Forbidden { user: String, action: Action },
+    /// The store refused the request, e.g. because it breaks the rules of the ticket's project.
+    #[error(transparent)]
+    Rejected(StoreError),
+}
+
+impl From<StoreError> for ClientError {
+    fn from(error: StoreError) -> Self {
+        match error {
+            StoreError::NotFound(id) => ClientError::NotFound(id),
+            error => ClientError::Rejected(error),
+        }
+    }
+}
+
+/// The server task panicked, or was cancelled by its runtime shutting down.
+/// The panic message is included, if there was one.
+#[derive(Debug, thiserror::Error)]
+#[error("The server panicked: {0}")]
+pub struct ServerPanicked(pub String);
+
+/// Controls the lifecycle of a server started with [`launch`].
+pub struct ServerHandle {
+    sender: mpsc::Sender<Command>,
+    task: JoinHandle<()>,
+}
+
+impl ServerHandle {
+    /// Stop the server once it has processed the commands that are already queued,
+    /// then wait for it to exit.
+    /// Commands sent after the shutdown request fail with [`ClientError::Disconnected`].
+    pub async fn shutdown(self) -> Result<(), ServerPanicked> {
+        // If the server is already gone, `join` will tell us why.
+        let _ = self.sender.send(Command::Shutdown).await;
+        self.join().await
+    }
+
+    /// Wait for the server to exit, which happens when every client has been dropped.
+    pub async fn join(self) -> Result<(), ServerPanicked> {
+        let ServerHandle { sender, task } = self;
+        drop(sender);
+        task.await.map_err(|error| {
+            if !error.is_panic() {
+                return ServerPanicked(error.to_string());
+            }
+            let payload = error.into_panic();
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            ServerPanicked(message)
+        })
+    }
+}
+
+/// Start a server that can queue up to `capacity` requests, with an empty store.
+///
+/// # Panics
+///
+/// Panics if called outside of a tokio runtime, or if `capacity` is zero.
+pub fn launch(capacity: usize) -> (TicketStoreClient, ServerHandle) {
+    launch_with_store(capacity, TicketStore::new())
+}
+
+/// Like [`launch`], serving `store`, e.g. one set up with a different
+/// [`IdGenerator`](crate::id::IdGenerator).
+pub fn launch_with_store(capacity: usize, store: TicketStore) -> (TicketStoreClient, ServerHandle) {
+    let (sender, receiver) = mpsc::channel(capacity);
+    let task = tokio::spawn(server(store, receiver));
+    let handle = ServerHandle {
+        sender: sender.clone(),
+        task,
+    };
+    (TicketStoreClient { sender }, handle)
+}
+
+enum Command {
+    Insert {
+        user: User,
+        draft: TicketDraft,
+        response_channel: oneshot::Sender<Result<Ticket, ClientError>>,
+    },
+    Get {
+        user: User,
+        id: TicketId,
+        response_channel: oneshot::Sender<Result<Ticket, ClientError>>,
+    },
+    Find {
+        user: User,
+        key: TicketKey,
+        response_channel: oneshot::Sender<Result<Ticket, ClientError>>,
+    },
+    Update {
+        user: User,
+        patch: TicketPatch,
+        response_channel: oneshot::Sender<Result<Ticket, ClientError>>,
+    },
+    Remove {
+        user: User,
+        id: TicketId,
+        response_channel: oneshot::Sender<Result<Ticket, ClientError>>,
+    },
+    List {
+        user: User,
+        filter: TicketFilter,
+        response_channel: oneshot::Sender<Result<Vec<Ticket>, ClientError>>,
+    },
+    AddProject {
+        user: User,
+        project: Project,
+        response_channel: oneshot::Sender<Result<(), ClientError>>,
+    },
+    Projects {
+        user: User,
+        response_channel: oneshot::Sender<Result<Vec<Project>, ClientError>>,
+    },
+    AuditLog {
+        user: User,
+        response_channel: oneshot::Sender<Result<Vec<AuditEntry>, ClientError>>,
+    },
+    Shutdown,
+}
+
+async fn server(mut store: TicketStore, mut receiver: mpsc::Receiver<Command>) {
+    let mut audit_log = AuditLog::default();
+    // `None` means there are no more senders: we can safely shut down the server.
+    while let Some(command) = receiver.recv().await {
+        // Replies are ignored if the client has given up on them.
+        match command {
+            Command::Insert {
+                user,
+                draft,
+                response_channel,
+            } => {
+                let ticket = user.authorize(Action::Create, None).and_then(|()| {
+                    let ticket = store.add_ticket(draft, user.name.clone())?;
+                    audit_log.record(&user, Action::Create, ticket.id);
+                    Ok(ticket.clone())
+                });
+                let _ = response_channel.send(ticket);
+            }
+            Command::Get {
+                user,
+                id,
+                response_channel,
+            } => {
+                let ticket = user
+                    .authorize(Action::Read, None)
+                    .and_then(|()| store.get(id).cloned().ok_or(ClientError::NotFound(id)));
+                let _ = response_channel.send(ticket);
+            }
+            Command::Find {
+                user,
+                key,
+                response_channel,
+            } => {
+                let ticket = user.authorize(Action::Read, None).and_then(|()| {
+                    let ticket = store.find(&key).ok_or(StoreError::UnknownTicket(key))?;
+                    Ok(ticket.clone())
+                });
+                let _ = response_channel.send(ticket);
+            }
+            Command::Update {
+                user,
+                patch,
+                response_channel,
+            } => {
+                let ticket = update(&mut store, &mut audit_log, &user, patch);
+                let _ = response_channel.send(ticket);
+            }
+            Command::Remove {
+                user,
+                id,
+                response_channel,
+            } => {
+                let ticket = user.authorize(Action::Remove, None).and_then(|()| {
+                    let ticket = store.remove(id).ok_or(ClientError::NotFound(id))?;
+                    audit_log.record(&user, Action::Remove, id);
+                    Ok(ticket)
+                });
+                let _ = response_channel.send(ticket);
+            }
+            Command::List {
+                user,
+                filter,
+                response_channel,
+            } => {
+                let tickets = user
+                    .authorize(Action::Read, None)
+                    .map(|()| store.list(&filter).cloned().collect());
+                let _ = response_channel.send(tickets);
+            }
+            Command::AddProject {
+                user,
+                project,
+                response_channel,
+            } => {
+                let added = user.authorize(Action::ManageProjects, None).and_then(|()| {
+                    let key = project.key.clone();
+                    store.add_project(project)?;
+                    audit_log.record(&user, Action::ManageProjects, key);
+                    Ok(())
+                });
+                let _ = response_channel.send(added);
+            }
+            Command::Projects {
+                user,
+                response_channel,
+            } => {
+                let projects = user
+                    .authorize(Action::Read, None)
+                    .map(|()| store.projects().cloned().collect());
+                let _ = response_channel.send(projects);
+            }
+            Command::AuditLog {
+                user,
+                response_channel,
+            } => {
+                let entries = user
+                    .authorize(Action::ReadAuditLog, None)
+                    .map(|()| audit_log.entries().to_vec());
+                let _ = response_channel.send(entries);
+            }
+            Command::Shutdown => break,
+        }
+    }
+}
+
+/// Apply `patch` if `user` may make every change in it, recording them in `audit_log`.
+fn update(
+    store: &mut TicketStore,
+    audit_log: &mut AuditLog,
+    user: &User,
+    patch: TicketPatch,
+) -> Result<Ticket, ClientError> {
+    let id = patch.id;
+    let ticket = store.get(id).ok_or(ClientError::NotFound(id))?;
+    user.authorize(Action::Read, Some(ticket))?;
+    // Permissions are checked against the ticket as it is before the change:
+    // assigning yourself a ticket and closing it takes two updates.
+    let mut actions = Vec::new();
+    if patch.title.is_some() || patch.description.is_some() || !patch.custom_fields.is_empty() {
+        actions.push(Action::Edit);
+    }
+    if patch.assignee.is_some() {
+        actions.push(Action::Assign);
+    }
+    match patch.status {
+        Some(status) if status == ticket.status => {}
+        Some(Status::Done) => actions.push(Action::Close),
+        Some(_) => actions.push(Action::Move),
+        None => {}
+    }
+    for action in &actions {
+        user.authorize(*action, Some(ticket))?;
+    }
+
+    let ticket = store.update(patch)?.clone();
+    for action in actions {
+        audit_log.record(user, action, id);
+    }
+    Ok(ticket)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::Subject;
+    use crate::clock::Clock;
+    use crate::custom_fields::{
+        CustomFieldChanges, CustomFieldError, CustomFields, FieldKind, TicketType,
+    };
+    use crate::project::{FieldPolicy, ProjectKey, Workflow};
+    use crate::users::Role;
+    use ticket_fields::test_helpers::{ticket_description, ticket_title};
+
+    pub(crate) fn core() -> ProjectKey {
+        ProjectKey::try_from("CORE").unwrap()
+    }
+
+    /// A server with a `CORE` project.
+    async fn start(capacity: usize) -> (TicketStoreClient, ServerHandle) {
+        let (client, server) = launch(capacity);
+        client
+            .add_project(&admin(), Project::new(core(), "Core"))
+            .await
+            .unwrap();
+        (client, server)
+    }
+
+    /// A valid draft for the `CORE` project.
+    pub(crate) fn draft() -> TicketDraft {
+        TicketDraft {
+            project: core(),
+            title: ticket_title(),
+            description: ticket_description(),
+            ticket_type: None,
+            custom_fields: CustomFields::new(),
+        }
+    }
+
+    fn admin() -> User {
+        User::new("root", Role::Admin)
+    }
+
+    fn moved_to(id: TicketId, status: Status) -> TicketPatch {
+        TicketPatch {
+            id,
+            title: None,
+            description: None,
+            status: Some(status),
+            assignee: None,
+            custom_fields: CustomFieldChanges::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn works() {
+        let (client, _server) = start(5).await;
+        let admin = admin();
+        let draft = draft();
+        let inserted = client.insert(&admin, draft.clone()).await.unwrap();
+        let id = inserted.id;
+
+        let ticket = client.clone().get(&admin, id).await.unwrap();
+        assert_eq!(ticket, inserted);
+        assert_eq!(ticket.status, Status::ToDo);
+        assert_eq!(ticket.title, draft.title);
+        assert_eq!(ticket.description, draft.description);
+        assert_eq!(ticket.reporter, admin.name);
+
+        let ticket = client
+            .update(&admin, moved_to(id, Status::InProgress))
+            .await
+            .unwrap();
+        assert_eq!(ticket.status, Status::InProgress);
+        assert_eq!(client.get(&admin, id).await.unwrap(), ticket);
+    }
+
+    #[tokio::test]
+    async fn list_and_remove() {
+        let (client, _server) = start(5).await;
+        let admin = admin();
+        let first = client.insert(&admin, draft()).await.unwrap().id;
+        let second = client.insert(&admin, draft()).await.unwrap().id;
+        client
+            .update(&admin, moved_to(second, Status::Done))
+            .await
+            .unwrap();
+
+        let done = TicketFilter {
+            status: Some(Status::Done),
+            ..TicketFilter::default()
+        };
+        let done = client.list(&admin, done).await.unwrap();
+        assert_eq!(done.iter().map(|t| t.id).collect::<Vec<_>>(), [second]);
+        let all = TicketFilter::default();
+        assert_eq!(client.list(&admin, all.clone()).await.unwrap().len(), 2);
+
+        assert_eq!(client.remove(&admin, first).await.unwrap().id, first);
+        assert!(matches!(
+            client.remove(&admin, first).await,
+            Err(ClientError::NotFound(_))
+        ));
+        assert_eq!(client.list(&admin, all).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn a_full_queue_is_reported_as_overloaded() {
+        // On a current-thread runtime, the server doesn't get to run
+        // while both requests are being queued.
+        let (client, _server) = start(1).await;
+        let admin = admin();
+        let (first, second) = tokio::join!(
+            client.insert(&admin, draft()),
+            client.insert(&admin, draft())
+        );
+        assert!(first.is_ok());
+        assert!(matches!(second, Err(ClientError::Overloaded)));
+    }
+
+    #[tokio::test]
+    async fn missing_tickets_are_reported() {
+        let (client, _server) = start(5).await;
+        let admin = admin();
+        let id = client.insert(&admin, draft()).await.unwrap().id;
+        let (other, _other_server) = start(5).await;
+        other.insert(&admin, draft()).await.unwrap();
+        let missing = other.insert(&admin, draft()).await.unwrap().id;
+
+        assert!(matches!(
+            client.get(&admin, missing).await,
+            Err(ClientError::NotFound(found)) if found == missing
+        ));
+        assert!(client.get(&admin, id).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn shutdown() {
+        let (client, server) = start(5).await;
+        let id = client.insert(&admin(), draft()).await.unwrap().id;
+
+        server.shutdown().await.unwrap();
+
+        assert!(matches!(
+            client.get(&admin(), id).await,
+            Err(ClientError::Disconnected)
+        ));
+    }
+
+    #[tokio::test]
+    async fn only_the_assignee_or_an_admin_may_close_a_ticket() {
+        let (client, _server) = start(5).await;
+        let reporter = User::new("rita", Role::Reporter);
+        let developer = User::new("dev", Role::Developer);
+        let other = User::new("other", Role::Developer);
+        let id = client.insert(&reporter, draft()).await.unwrap().id;
+
+        let assign = TicketPatch {
+            assignee: Some(developer.name.clone()),
+            ..moved_to(id, Status::InProgress)
+        };
+        assert!(matches!(
+            client.update(&reporter, assign.clone()).await,
+            Err(ClientError::Forbidden {
+                action: Action::Assign,
+                ..
+            })
+        ));
+        client.update(&developer, assign).await.unwrap();
+
+        assert!(matches!(
+            client.update(&other, moved_to(id, Status::Done)).await,
+            Err(ClientError::Forbidden {
+                action: Action::Close,
+                ..
+            })
+        ));
+        let closed = client
+            .update(&developer, moved_to(id, Status::Done))
+            .await
+            .unwrap();
+        assert_eq!(closed.status, Status::Done);
+        client
+            .update(&admin(), moved_to(id, Status::ToDo))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn viewers_can_only_read() {
+        let (client, _server) = start(5).await;
+        let viewer = User::new("vic", Role::Viewer);
+        let id = client.insert(&admin(), draft()).await.unwrap().id;
+
+        assert!(client.get(&viewer, id).await.is_ok());
+        assert!(matches!(
+            client.insert(&viewer, draft()).await,
+            Err(ClientError::Forbidden {
+                action: Action::Create,
+                ..
+            })
+        ));
+        assert!(matches!(
+            client.remove(&viewer, id).await,
+            Err(ClientError::Forbidden {
+                action: Action::Remove,
+                ..
+            })
+        ));
+        assert!(matches!(
+            client.audit_log(&viewer).await,
+            Err(ClientError::Forbidden { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn changes_are_audited() {
+        let (client, _server) = start(5).await;
+        let reporter = User::new("rita", Role::Reporter);
+        let id = client.insert(&reporter, draft()).await.unwrap().id;
+        let edit = TicketPatch {
+            title: Some(ticket_title()),
+            status: None,
+            ..moved_to(id, Status::ToDo)
+        };
+        client.update(&reporter, edit).await.unwrap();
+        // Denied requests don't change anything, so they aren't recorded.
+        let _ = client.remove(&reporter, id).await;
+        client.remove(&admin(), id).await.unwrap();
+
+        let entries = client.audit_log(&admin()).await.unwrap();
+        let entries: Vec<_> = entries
+            .iter()
+            .map(|entry| (entry.user.as_str(), entry.action, entry.subject.clone()))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                ("root", Action::ManageProjects, Subject::Project(core())),
+                ("rita", Action::Create, Subject::Ticket(id)),
+                ("rita", Action::Edit, Subject::Ticket(id)),
+                ("root", Action::Remove, Subject::Ticket(id)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn each_project_numbers_its_own_tickets() {
+        let (client, _server) = start(5).await;
+        let admin = admin();
+        let web = ProjectKey::try_from("WEB").unwrap();
+        client
+            .add_project(&admin, Project::new(web.clone(), "Website"))
+            .await
+            .unwrap();
+        assert!(matches!(
+            client
+                .add_project(&admin, Project::new(web.clone(), "Again"))
+                .await,
+            Err(ClientError::Rejected(StoreError::ProjectExists(_)))
+        ));
+
+        client.insert(&admin, draft()).await.unwrap();
+        let id = client
+            .insert(
+                &admin,
+                TicketDraft {
+                    project: web.clone(),
+                    ..draft()
+                },
+            )
+            .await
+            .unwrap()
+            .id;
+        let second = client.insert(&admin, draft()).await.unwrap().id;
+
+        let key = client.get(&admin, id).await.unwrap().key;
+        assert_eq!(key.to_string(), "WEB-1");
+        let found = client
+            .find(&admin, "CORE-2".parse().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(found.id, second);
+
+        let filter = TicketFilter {
+            project: Some(web),
+            ..TicketFilter::default()
+        };
+        assert_eq!(client.list(&admin, filter).await.unwrap().len(), 1);
+        assert!(matches!(
+            client
+                .insert(
+                    &admin,
+                    TicketDraft {
+                        project: ProjectKey::try_from("NOPE").unwrap(),
+                        ..draft()
+                    }
+                )
+                .await,
+            Err(ClientError::Rejected(StoreError::UnknownProject(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn projects_enforce_their_workflow_and_field_policy() {
+        let (client, _server) = launch(5);
+        let admin = admin();
+        let strict = Project::new(core(), "Strict")
+            .with_workflow(Workflow::new([
+                (Status::ToDo, Status::InProgress),
+                (Status::InProgress, Status::Done),
+            ]))
+            .with_field_policy(FieldPolicy {
+                assignee_required: [Status::InProgress].into(),
+                max_title_length: None,
+            });
+        client.add_project(&admin, strict).await.unwrap();
+        let id = client.insert(&admin, draft()).await.unwrap().id;
+
+        assert!(matches!(
+            client.update(&admin, moved_to(id, Status::Done)).await,
+            Err(ClientError::Rejected(
+                StoreError::TransitionNotAllowed { .. }
+            ))
+        ));
+        assert!(matches!(
+            client
+                .update(&admin, moved_to(id, Status::InProgress))
+                .await,
+            Err(ClientError::Rejected(StoreError::AssigneeRequired { .. }))
+        ));
+        let started = TicketPatch {
+            assignee: Some(admin.name.clone()),
+            ..moved_to(id, Status::InProgress)
+        };
+        assert_eq!(
+            client.update(&admin, started).await.unwrap().status,
+            Status::InProgress
+        );
+    }
+
+    #[tokio::test]
+    async fn rejected_drafts_dont_use_up_an_id() {
+        let (client, _server) = launch(5);
+        let admin = admin();
+        let terse = Project::new(core(), "Terse").with_field_policy(FieldPolicy {
+            assignee_required: [].into(),
+            max_title_length: Some(5),
+        });
+        client.add_project(&admin, terse).await.unwrap();
+        assert!(matches!(
+            client.insert(&admin, draft()).await,
+            Err(ClientError::Rejected(StoreError::TitleTooLong { .. }))
+        ));
+
+        let short = TicketDraft {
+            title: "Short".to_string().try_into().unwrap(),
+            ..draft()
+        };
+        let ticket = client.insert(&admin, short).await.unwrap();
+        assert_eq!(ticket.id.to_string(), "0");
+        assert_eq!(ticket.key.number, 1);
+    }
+
+    #[tokio::test]
+    async fn the_id_format_is_chosen_with_the_store() {
+        let store = TicketStore::new().with_id_generator(crate::id::IdGenerator::snowflake(1));
+        let (client, _server) = launch_with_store(5, store);
+        client
+            .add_project(&admin(), Project::new(core(), "Core"))
+            .await
+            .unwrap();
+        let id = client.insert(&admin(), draft()).await.unwrap().id;
+        // Snowflake ids are large: they start with a timestamp.
+        assert!(id.to_string().len() > 10);
+
+        let parsed: TicketId = id.to_string().parse().unwrap();
+        assert_eq!(client.get(&admin(), parsed).await.unwrap().id, id);
+    }
+
+    #[tokio::test]
+    async fn changes_are_timestamped_and_status_changes_recorded() {
+        let created = "2024-05-06T09:00:00Z".parse().unwrap();
+        let clock = crate::clock::ManualClock::new(created);
+        let (client, _server) = launch_with_store(5, TicketStore::new().with_clock(clock.clone()));
+        let admin = admin();
+        client
+            .add_project(&admin, Project::new(core(), "Core"))
+            .await
+            .unwrap();
+        let id = client.insert(&admin, draft()).await.unwrap().id;
+
+        clock.advance(chrono::Duration::hours(1));
+        let closed = client
+            .update(&admin, moved_to(id, Status::Done))
+            .await
+            .unwrap();
+        assert_eq!(closed.created_at, created);
+        assert_eq!(closed.updated_at, clock.now());
+        assert_eq!(closed.closed_at, Some(clock.now()));
+
+        clock.advance(chrono::Duration::hours(1));
+        let reopened = client
+            .update(&admin, moved_to(id, Status::ToDo))
+            .await
+            .unwrap();
+        assert_eq!(reopened.created_at, created);
+        assert_eq!(reopened.updated_at, clock.now());
+        assert_eq!(reopened.closed_at, None);
+
+        let statuses: Vec<_> = reopened.history.iter().map(|t| t.status).collect();
+        assert_eq!(statuses, [Status::ToDo, Status::Done, Status::ToDo]);
+        let closing = created + chrono::Duration::minutes(90);
+        assert_eq!(reopened.status_at(closing), Some(Status::Done));
+        let before = created - chrono::Duration::seconds(1);
+        assert_eq!(reopened.status_at(before), None);
+    }
+
+    #[tokio::test]
+    async fn custom_fields_are_checked_and_can_be_filtered_on() {
+        let (client, _server) = launch(5);
+        let admin = admin();
+        let bug = TicketType::new("bug")
+            .with_field(
+                "severity",
+                FieldKind::Enum {
+                    options: vec!["low".into(), "high".into()],
+                },
+                true,
+            )
+            .with_field("component", FieldKind::String { max_length: None }, false);
+        client
+            .add_project(&admin, Project::new(core(), "Core").with_ticket_type(bug))
+            .await
+            .unwrap();
+        let bug = |severity: &str| TicketDraft {
+            ticket_type: Some("bug".into()),
+            custom_fields: CustomFields::new().with("severity", severity),
+            ..draft()
+        };
+
+        let id = client.insert(&admin, bug("low")).await.unwrap().id;
+        client.insert(&admin, bug("high")).await.unwrap();
+        client.insert(&admin, draft()).await.unwrap();
+        assert!(matches!(
+            client.insert(&admin, bug("meh")).await,
+            Err(ClientError::Rejected(StoreError::CustomField(
+                CustomFieldError::NotAnOption { .. }
+            )))
+        ));
+        assert!(matches!(
+            client
+                .insert(
+                    &admin,
+                    TicketDraft {
+                        ticket_type: None,
+                        ..bug("low")
+                    }
+                )
+                .await,
+            Err(ClientError::Rejected(StoreError::CustomField(
+                CustomFieldError::UnknownField(_)
+            )))
+        ));
+
+        let escalated = TicketPatch {
+            status: None,
+            custom_fields: CustomFieldChanges::new().with("severity", "high"),
+            ..moved_to(id, Status::ToDo)
+        };
+        client.update(&admin, escalated).await.unwrap();
+        let high = TicketFilter {
+            ticket_type: Some("bug".into()),
+            custom_fields: CustomFields::new().with("severity", "high"),
+            ..TicketFilter::default()
+        };
+        assert_eq!(client.list(&admin, high).await.unwrap().len(), 2);
+
+        let changed = |custom_fields| TicketPatch {
+            status: None,
+            custom_fields,
+            ..moved_to(id, Status::ToDo)
+        };
+        let assigned = changed(CustomFieldChanges::new().with("component", "parser"));
+        let ticket = client.update(&admin, assigned).await.unwrap();
+        assert_eq!(
+            ticket.custom_fields.get("component"),
+            Some(&"parser".into())
+        );
+        let unassigned = changed(CustomFieldChanges::new().without("component"));
+        let ticket = client.update(&admin, unassigned).await.unwrap();
+        assert_eq!(ticket.custom_fields.get("component"), None);
+        assert_eq!(ticket.custom_fields.get("severity"), Some(&"high".into()));
+        assert!(matches!(
+            client
+                .update(
+                    &admin,
+                    changed(CustomFieldChanges::new().without("severity"))
+                )
+                .await,
+            Err(ClientError::Rejected(StoreError::CustomField(
+                CustomFieldError::Missing(_)
+            )))
+        ));
+    }
+}
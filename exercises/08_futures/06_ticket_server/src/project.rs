@@ -0,0 +1,299 @@
+//! Projects let several teams share a store: each has its own key, ticket numbering,
+//! workflow and field policy.
+use std::collections::BTreeSet;
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::custom_fields::TicketType;
+use crate::data::Status;
+
+/// A short, uppercase name for a project, e.g. `CORE`. It prefixes the keys of its tickets.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ProjectKey(String);
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum ProjectKeyError {
+    #[error("The project key cannot be empty")]
+    Empty,
+    #[error("The project key cannot be longer than 10 characters")]
+    TooLong,
+    #[error(
+        "The project key must start with a letter, and only contain uppercase letters and digits"
+    )]
+    InvalidCharacters,
+}
+
+impl ProjectKey {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for ProjectKey {
+    type Error = ProjectKeyError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let mut chars = value.chars();
+        match chars.next() {
+            None => Err(ProjectKeyError::Empty),
+            Some(_) if value.len() > 10 => Err(ProjectKeyError::TooLong),
+            Some(first)
+                if first.is_ascii_uppercase()
+                    && chars.all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()) =>
+            {
+                Ok(Self(value))
+            }
+            Some(_) => Err(ProjectKeyError::InvalidCharacters),
+        }
+    }
+}
+
+impl TryFrom<&str> for ProjectKey {
+    type Error = ProjectKeyError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::try_from(value.to_string())
+    }
+}
+
+impl FromStr for ProjectKey {
+    type Err = ProjectKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
+    }
+}
+
+impl fmt::Display for ProjectKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The human-readable name of a ticket, e.g. `CORE-42`:
+/// the key of its project, and its number within the project.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TicketKey {
+    pub project: ProjectKey,
+    /// Numbers start at 1, and are never reused within a project.
+    pub number: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum TicketKeyError {
+    #[error("A ticket key is a project key and a number, separated by a dash")]
+    MissingSeparator,
+    #[error(transparent)]
+    Project(#[from] ProjectKeyError),
+    #[error("Invalid ticket number: {0}")]
+    Number(#[from] ParseIntError),
+    /// E.g. `CORE-+5` or `CORE-05`: each ticket has a single key.
+    #[error("Ticket numbers are written in plain digits, without leading zeros")]
+    NonCanonicalNumber,
+    #[error("Ticket numbers start at 1")]
+    Zero,
+}
+
+impl fmt::Display for TicketKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.project, self.number)
+    }
+}
+
+impl FromStr for TicketKey {
+    type Err = TicketKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (project, digits) = s.split_once('-').ok_or(TicketKeyError::MissingSeparator)?;
+        let project = project.parse()?;
+        let number = digits.parse()?;
+        if number == 0 {
+            return Err(TicketKeyError::Zero);
+        }
+        // `u64::from_str` also accepts a leading `+`.
+        if digits.starts_with('0') || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(TicketKeyError::NonCanonicalNumber);
+        }
+        Ok(Self { project, number })
+    }
+}
+
+impl TryFrom<String> for TicketKey {
+    type Error = TicketKeyError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<TicketKey> for String {
+    fn from(key: TicketKey) -> Self {
+        key.to_string()
+    }
+}
+
+/// The status changes a project allows.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Workflow {
+    /// `None` allows every change.
+    transitions: Option<BTreeSet<(Status, Status)>>,
+}
+
+impl Workflow {
+    /// Tickets can go from any status to any other.
+    pub fn unrestricted() -> Self {
+        Self { transitions: None }
+    }
+
+    /// Tickets can only go from one status to another along the given `(from, to)` pairs.
+    pub fn new(transitions: impl IntoIterator<Item = (Status, Status)>) -> Self {
+        Self {
+            transitions: Some(transitions.into_iter().collect()),
+        }
+    }
+
+    /// Staying in the same status is always allowed.
+    pub fn allows(&self, from: Status, to: Status) -> bool {
+        from == to
+            || self
+                .transitions
+                .as_ref()
+                .is_none_or(|transitions| transitions.contains(&(from, to)))
+    }
+}
+
+impl Default for Workflow {
+    fn default() -> Self {
+        Self::unrestricted()
+    }
+}
+
+/// Rules a project's tickets must follow, on top of those of their fields' types.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FieldPolicy {
+    /// Tickets in these statuses must have an assignee.
+    pub assignee_required: BTreeSet<Status>,
+    /// A stricter limit than [`TicketTitle`](ticket_fields::TicketTitle)'s, in bytes.
+    pub max_title_length: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Project {
+    pub key: ProjectKey,
+    pub name: String,
+    #[serde(default)]
+    pub workflow: Workflow,
+    #[serde(default)]
+    pub field_policy: FieldPolicy,
+    /// The types of ticket the project supports, and their custom fields.
+    #[serde(default)]
+    pub ticket_types: Vec<TicketType>,
+}
+
+impl Project {
+    /// A project with an unrestricted workflow, no field policy and no ticket types.
+    pub fn new(key: ProjectKey, name: impl Into<String>) -> Self {
+        Self {
+            key,
+            name: name.into(),
+            workflow: Workflow::default(),
+            field_policy: FieldPolicy::default(),
+            ticket_types: Vec::new(),
+        }
+    }
+
+    pub fn with_workflow(self, workflow: Workflow) -> Self {
+        Self { workflow, ..self }
+    }
+
+    pub fn with_field_policy(self, field_policy: FieldPolicy) -> Self {
+        Self {
+            field_policy,
+            ..self
+        }
+    }
+
+    pub fn with_ticket_type(mut self, ticket_type: TicketType) -> Self {
+        self.ticket_types.push(ticket_type);
+        self
+    }
+
+    pub fn ticket_type(&self, name: &str) -> Option<&TicketType> {
+        self.ticket_types
+            .iter()
+            .find(|ticket_type| ticket_type.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ticket_keys_round_trip() {
+        let key: TicketKey = "CORE-42".parse().unwrap();
+        assert_eq!(key.project.as_str(), "CORE");
+        assert_eq!(key.number, 42);
+        assert_eq!(key.to_string(), "CORE-42");
+
+        let json = serde_json::to_string(&key).unwrap();
+        assert_eq!(json, "\"CORE-42\"");
+        assert_eq!(serde_json::from_str::<TicketKey>(&json).unwrap(), key);
+    }
+
+    #[test]
+    fn invalid_keys_are_rejected() {
+        assert_eq!(
+            "CORE".parse::<TicketKey>(),
+            Err(TicketKeyError::MissingSeparator)
+        );
+        assert_eq!(
+            "core-1".parse::<TicketKey>(),
+            Err(TicketKeyError::Project(ProjectKeyError::InvalidCharacters))
+        );
+        assert_eq!(
+            "-1".parse::<TicketKey>(),
+            Err(TicketKeyError::Project(ProjectKeyError::Empty))
+        );
+        assert!(matches!(
+            "CORE-x".parse::<TicketKey>(),
+            Err(TicketKeyError::Number(_))
+        ));
+        assert!(matches!(
+            "CORE-".parse::<TicketKey>(),
+            Err(TicketKeyError::Number(_))
+        ));
+        assert_eq!(
+            "CORE-+5".parse::<TicketKey>(),
+            Err(TicketKeyError::NonCanonicalNumber)
+        );
+        assert_eq!(
+            "CORE-05".parse::<TicketKey>(),
+            Err(TicketKeyError::NonCanonicalNumber)
+        );
+        assert_eq!("CORE-0".parse::<TicketKey>(), Err(TicketKeyError::Zero));
+        assert!(serde_json::from_str::<TicketKey>("\"CORE-05\"").is_err());
+        assert_eq!(
+            ProjectKey::try_from("ABCDEFGHIJK"),
+            Err(ProjectKeyError::TooLong)
+        );
+    }
+
+    #[test]
+    fn workflows_restrict_status_changes() {
+        let workflow = Workflow::new([
+            (Status::ToDo, Status::InProgress),
+            (Status::InProgress, Status::Done),
+        ]);
+        assert!(workflow.allows(Status::ToDo, Status::InProgress));
+        assert!(workflow.allows(Status::Done, Status::Done));
+        assert!(!workflow.allows(Status::ToDo, Status::Done));
+        assert!(Workflow::unrestricted().allows(Status::Done, Status::ToDo));
+    }
+}
@@ -0,0 +1,106 @@
+//! The wire format spoken by [`serve`](crate::remote::serve) and
+//! [`RemoteClient`](crate::remote::RemoteClient).
+//!
+//! Messages are JSON documents, one per line. The client sends a [`Request`] and
+//! waits for the matching [`Response`] before sending the next one, so there's no
+//! need to tag messages with an id. Requests longer than [`MAX_LINE_LENGTH`] are
+//! rejected, and the connection is closed.
+//!
+//! A connection starts with a [`Request::Login`]: every other request is made on behalf
+//! of the user who logged in, and is rejected with [`Response::Unauthorized`] until then.
+//! Passwords are sent in clear text, so only use this over a trusted network.
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::audit::AuditEntry;
+use crate::data::{Ticket, TicketDraft, TicketFilter, TicketPatch};
+use crate::project::{Project, TicketKey};
+use crate::store::TicketId;
+use crate::users::User;
+use crate::ClientError;
+
+/// The longest request the server accepts, in bytes, line ending excluded.
+pub const MAX_LINE_LENGTH: usize = 1024 * 1024;
+
+/// Mirrors the server's `Command`s, minus the users and the response channels:
+/// requests are made by the user logged in on the connection, and the reply goes back
+/// on the same connection.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Request {
+    Login {
+        username: String,
+        password: String,
+    },
+    Insert {
+        draft: TicketDraft,
+    },
+    Get {
+        id: TicketId,
+    },
+    Find {
+        key: TicketKey,
+    },
+    Update {
+        patch: TicketPatch,
+    },
+    Remove {
+        id: TicketId,
+    },
+    List {
+        #[serde(flatten)]
+        filter: TicketFilter,
+    },
+    AuditLog,
+    AddProject {
+        project: Project,
+    },
+    Projects,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum Response {
+    LoggedIn {
+        user: User,
+    },
+    Inserted {
+        id: TicketId,
+    },
+    Ticket {
+        ticket: Ticket,
+    },
+    Tickets {
+        tickets: Vec<Ticket>,
+    },
+    AuditLog {
+        entries: Vec<AuditEntry>,
+    },
+    ProjectAdded,
+    Projects {
+        projects: Vec<Project>,
+    },
+    /// The store couldn't process the request.
+    Failed {
+        error: ClientError,
+    },
+    /// The login failed, or the request was sent before logging in.
+    Unauthorized {
+        reason: String,
+    },
+    /// The request couldn't be parsed.
+    BadRequest {
+        reason: String,
+    },
+}
+
+/// Write `message` as a single line of JSON.
+pub(crate) async fn write_line<W, T>(writer: &mut W, message: &T) -> std::io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+    T: Serialize,
+{
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    writer.write_all(&line).await
+}
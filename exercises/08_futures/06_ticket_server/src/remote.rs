@@ -0,0 +1,513 @@
+//! Reaching the ticket store over TCP, using the [`protocol`](crate::protocol).
+use std::sync::Arc;
+
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader, Lines};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::sync::Mutex;
+
+use crate::audit::AuditEntry;
+use crate::auth::Authenticator;
+use crate::data::{Ticket, TicketDraft, TicketFilter, TicketPatch};
+use crate::project::{Project, TicketKey};
+use crate::protocol::{write_line, Request, Response, MAX_LINE_LENGTH};
+use crate::store::TicketId;
+use crate::users::User;
+use crate::{ClientError, TicketStoreClient};
+
+/// Accept connections on `listener` forever, serving each of them on its own task.
+/// Users log in through `authenticator`, then their requests are forwarded
+/// to the store behind `client`.
+pub async fn serve(
+    listener: TcpListener,
+    client: TicketStoreClient,
+    authenticator: Arc<dyn Authenticator>,
+) -> std::io::Result<()> {
+    loop {
+        let (socket, _addr) = listener.accept().await?;
+        let client = client.clone();
+        let authenticator = authenticator.clone();
+        tokio::spawn(async move {
+            // An I/O error only affects this connection: we just drop it.
+            let _ = handle_connection(socket, client, authenticator).await;
+        });
+    }
+}
+
+async fn handle_connection(
+    socket: TcpStream,
+    client: TicketStoreClient,
+    authenticator: Arc<dyn Authenticator>,
+) -> std::io::Result<()> {
+    let (reader, mut writer) = socket.into_split();
+    let mut reader = BufReader::new(reader);
+    let mut line = Vec::new();
+    let mut user = None;
+    loop {
+        line.clear();
+        // Reading one byte past the limit tells lines that are too long from lines that aren't.
+        let read = (&mut reader)
+            .take(MAX_LINE_LENGTH as u64 + 1)
+            .read_until(b'\n', &mut line)
+            .await?;
+        if read == 0 {
+            break;
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        } else if line.len() > MAX_LINE_LENGTH {
+            // The rest of the line is still to be read: there's no telling where the next
+            // request starts, so we give up on the connection.
+            let response = Response::BadRequest {
+                reason: format!("Requests cannot be longer than {MAX_LINE_LENGTH} bytes"),
+            };
+            write_line(&mut writer, &response).await?;
+            break;
+        }
+        let response = match (serde_json::from_slice(&line), &user) {
+            (Ok(Request::Login { username, password }), _) => {
+                let authenticator = authenticator.clone();
+                let outcome = tokio::task::spawn_blocking(move || {
+                    authenticator.authenticate(&username, &password)
+                })
+                .await?;
+                match outcome {
+                    Ok(logged_in) => {
+                        user = Some(logged_in.clone());
+                        Response::LoggedIn { user: logged_in }
+                    }
+                    Err(e) => {
+                        // Whoever was logged in before is logged out: the connection
+                        // is now on behalf of nobody, not of the previous user.
+                        user = None;
+                        Response::Unauthorized {
+                            reason: e.to_string(),
+                        }
+                    }
+                }
+            }
+            (Ok(request), Some(user)) => dispatch(&client, user, request).await,
+            (Ok(_), None) => Response::Unauthorized {
+                reason: "Log in first".into(),
+            },
+            (Err(e), _) => Response::BadRequest {
+                reason: e.to_string(),
+            },
+        };
+        write_line(&mut writer, &response).await?;
+    }
+    Ok(())
+}
+
+async fn dispatch(client: &TicketStoreClient, user: &User, request: Request) -> Response {
+    let outcome = match request {
+        Request::Login { .. } => unreachable!("logins are handled by the connection"),
+        Request::Insert { draft } => client
+            .insert(user, draft)
+            .await
+            .map(|ticket| Response::Inserted { id: ticket.id }),
+        Request::Get { id } => client
+            .get(user, id)
+            .await
+            .map(|ticket| Response::Ticket { ticket }),
+        Request::Find { key } => client
+            .find(user, key)
+            .await
+            .map(|ticket| Response::Ticket { ticket }),
+        Request::Update { patch } => client
+            .update(user, patch)
+            .await
+            .map(|ticket| Response::Ticket { ticket }),
+        Request::Remove { id } => client
+            .remove(user, id)
+            .await
+            .map(|ticket| Response::Ticket { ticket }),
+        Request::List { filter } => client
+            .list(user, filter)
+            .await
+            .map(|tickets| Response::Tickets { tickets }),
+        Request::AuditLog => client
+            .audit_log(user)
+            .await
+            .map(|entries| Response::AuditLog { entries }),
+        Request::AddProject { project } => client
+            .add_project(user, project)
+            .await
+            .map(|()| Response::ProjectAdded),
+        Request::Projects => client
+            .projects(user)
+            .await
+            .map(|projects| Response::Projects { projects }),
+    };
+    outcome.unwrap_or_else(|error| Response::Failed { error })
+}
+
+/// Everything that can go wrong when talking to a remote server.
+#[derive(Debug, thiserror::Error)]
+pub enum RemoteError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("The server closed the connection")]
+    ConnectionClosed,
+    /// An earlier request on the connection was cancelled, or failed, before its response
+    /// was read: there's no telling which response belongs to which request anymore.
+    /// Connect again.
+    #[error("An earlier request didn't complete: the connection can't be used anymore")]
+    Abandoned,
+    #[error("Malformed message: {0}")]
+    Protocol(#[from] serde_json::Error),
+    #[error("The server rejected the request: {0}")]
+    BadRequest(String),
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+    #[error("The server replied with an unexpected message: {0:?}")]
+    UnexpectedResponse(Response),
+    #[error(transparent)]
+    Client(#[from] ClientError),
+}
+
+/// The TCP counterpart of [`TicketStoreClient`].
+///
+/// Requests made through the same client are sent one at a time, over a single connection,
+/// on behalf of the user who [logged in](Self::login) on it.
+///
+/// Dropping a request's future before it completes, e.g. on a timeout, gives up on the
+/// connection: every later request fails with [`RemoteError::Abandoned`].
+pub struct RemoteClient {
+    connection: Mutex<Connection>,
+}
+
+struct Connection {
+    lines: Lines<BufReader<OwnedReadHalf>>,
+    writer: OwnedWriteHalf,
+    /// Set while a request is being sent or its response read.
+    /// Still set when a call starts if the previous one didn't complete.
+    in_flight: bool,
+}
+
+impl RemoteClient {
+    pub async fn connect(addr: impl ToSocketAddrs) -> Result<Self, RemoteError> {
+        let (reader, writer) = TcpStream::connect(addr).await?.into_split();
+        let connection = Connection {
+            lines: BufReader::new(reader).lines(),
+            writer,
+            in_flight: false,
+        };
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    pub async fn login(&self, username: &str, password: &str) -> Result<User, RemoteError> {
+        let request = Request::Login {
+            username: username.to_string(),
+            password: password.to_string(),
+        };
+        match self.call(request).await? {
+            Response::LoggedIn { user } => Ok(user),
+            response => Err(RemoteError::UnexpectedResponse(response)),
+        }
+    }
+
+    pub async fn insert(&self, draft: TicketDraft) -> Result<TicketId, RemoteError> {
+        match self.call(Request::Insert { draft }).await? {
+            Response::Inserted { id } => Ok(id),
+            response => Err(RemoteError::UnexpectedResponse(response)),
+        }
+    }
+
+    pub async fn get(&self, id: TicketId) -> Result<Ticket, RemoteError> {
+        match self.call(Request::Get { id }).await? {
+            Response::Ticket { ticket } => Ok(ticket),
+            response => Err(RemoteError::UnexpectedResponse(response)),
+        }
+    }
+
+    pub async fn find(&self, key: TicketKey) -> Result<Ticket, RemoteError> {
+        match self.call(Request::Find { key }).await? {
+            Response::Ticket { ticket } => Ok(ticket),
+            response => Err(RemoteError::UnexpectedResponse(response)),
+        }
+    }
+
+    /// Apply `patch`, returning the updated ticket.
+    pub async fn update(&self, patch: TicketPatch) -> Result<Ticket, RemoteError> {
+        match self.call(Request::Update { patch }).await? {
+            Response::Ticket { ticket } => Ok(ticket),
+            response => Err(RemoteError::UnexpectedResponse(response)),
+        }
+    }
+
+    /// Remove the ticket with the given `id`, returning it.
+    pub async fn remove(&self, id: TicketId) -> Result<Ticket, RemoteError> {
+        match self.call(Request::Remove { id }).await? {
+            Response::Ticket { ticket } => Ok(ticket),
+            response => Err(RemoteError::UnexpectedResponse(response)),
+        }
+    }
+
+    /// The tickets matching `filter`, in id order.
+    pub async fn list(&self, filter: TicketFilter) -> Result<Vec<Ticket>, RemoteError> {
+        match self.call(Request::List { filter }).await? {
+            Response::Tickets { tickets } => Ok(tickets),
+            response => Err(RemoteError::UnexpectedResponse(response)),
+        }
+    }
+
+    /// Every change made to the store so far, oldest first.
+    pub async fn audit_log(&self) -> Result<Vec<AuditEntry>, RemoteError> {
+        match self.call(Request::AuditLog).await? {
+            Response::AuditLog { entries } => Ok(entries),
+            response => Err(RemoteError::UnexpectedResponse(response)),
+        }
+    }
+
+    pub async fn add_project(&self, project: Project) -> Result<(), RemoteError> {
+        match self.call(Request::AddProject { project }).await? {
+            Response::ProjectAdded => Ok(()),
+            response => Err(RemoteError::UnexpectedResponse(response)),
+        }
+    }
+
+    /// Every project, in key order.
+    pub async fn projects(&self) -> Result<Vec<Project>, RemoteError> {
+        match self.call(Request::Projects).await? {
+            Response::Projects { projects } => Ok(projects),
+            response => Err(RemoteError::UnexpectedResponse(response)),
+        }
+    }
+
+    /// Send `request` and wait for the response, turning errors reported by the server
+    /// into [`RemoteError`]s.
+    async fn call(&self, request: Request) -> Result<Response, RemoteError> {
+        let mut connection = self.connection.lock().await;
+        if connection.in_flight {
+            return Err(RemoteError::Abandoned);
+        }
+        connection.in_flight = true;
+        write_line(&mut connection.writer, &request).await?;
+        let line = connection
+            .lines
+            .next_line()
+            .await?
+            .ok_or(RemoteError::ConnectionClosed)?;
+        connection.in_flight = false;
+        match serde_json::from_str(&line)? {
+            Response::Failed { error } => Err(error.into()),
+            Response::BadRequest { reason } => Err(RemoteError::BadRequest(reason)),
+            Response::Unauthorized { reason } => Err(RemoteError::Unauthorized(reason)),
+            response => Ok(response),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::LocalAuthenticator;
+    use crate::custom_fields::CustomFieldChanges;
+    use crate::data::Status;
+    use crate::launch;
+    use crate::tests::{core, draft};
+    use crate::users::Role;
+    use tokio::io::AsyncWriteExt;
+
+    async fn start() -> std::net::SocketAddr {
+        let (client, _server) = launch(5);
+        let mut authenticator = LocalAuthenticator::new();
+        authenticator
+            .add_user("admin", "secret", Role::Admin)
+            .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(serve(listener, client, Arc::new(authenticator)));
+        addr
+    }
+
+    /// A client connected to `addr`, logged in as an admin.
+    async fn connect(addr: std::net::SocketAddr) -> RemoteClient {
+        let client = RemoteClient::connect(addr).await.unwrap();
+        client.login("admin", "secret").await.unwrap();
+        client
+    }
+
+    #[tokio::test]
+    async fn round_trip() {
+        let addr = start().await;
+        let client = connect(addr).await;
+        client
+            .add_project(Project::new(core(), "Core"))
+            .await
+            .unwrap();
+        assert_eq!(client.projects().await.unwrap().len(), 1);
+
+        let draft = draft();
+        let id = client.insert(draft.clone()).await.unwrap();
+        let ticket = client.get(id).await.unwrap();
+        assert_eq!(ticket.title, draft.title);
+        assert_eq!(ticket.status, Status::ToDo);
+        assert_eq!(client.find(ticket.key).await.unwrap().id, id);
+
+        let patch = TicketPatch {
+            id,
+            title: None,
+            description: None,
+            status: Some(Status::Done),
+            assignee: None,
+            custom_fields: CustomFieldChanges::new(),
+        };
+        assert_eq!(client.update(patch).await.unwrap().status, Status::Done);
+
+        // Another connection sees the same store.
+        let other = connect(addr).await;
+        assert_eq!(other.get(id).await.unwrap().status, Status::Done);
+
+        let done = TicketFilter {
+            status: Some(Status::Done),
+            ..TicketFilter::default()
+        };
+        let done = other.list(done).await.unwrap();
+        assert_eq!(done.len(), 1);
+        assert_eq!(other.remove(id).await.unwrap().id, id);
+        assert!(client
+            .list(TicketFilter::default())
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(client.audit_log().await.unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn requests_need_a_login() {
+        let addr = start().await;
+        let client = RemoteClient::connect(addr).await.unwrap();
+        assert!(matches!(
+            client.list(TicketFilter::default()).await,
+            Err(RemoteError::Unauthorized(_))
+        ));
+        assert!(matches!(
+            client.login("admin", "wrong").await,
+            Err(RemoteError::Unauthorized(_))
+        ));
+        let user = client.login("admin", "secret").await.unwrap();
+        assert_eq!(user.role, Role::Admin);
+        assert!(client.projects().await.unwrap().is_empty());
+
+        assert!(matches!(
+            client.login("admin", "wrong").await,
+            Err(RemoteError::Unauthorized(_))
+        ));
+        assert!(matches!(
+            client.projects().await,
+            Err(RemoteError::Unauthorized(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn cancelled_requests_give_up_on_the_connection() {
+        // A server that never replies.
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = RemoteClient::connect(addr).await.unwrap();
+        let (_socket, _) = listener.accept().await.unwrap();
+
+        let timeout = std::time::Duration::from_millis(10);
+        assert!(tokio::time::timeout(timeout, client.projects())
+            .await
+            .is_err());
+        assert!(matches!(
+            client.projects().await,
+            Err(RemoteError::Abandoned)
+        ));
+    }
+
+    #[tokio::test]
+    async fn store_errors_are_forwarded() {
+        let addr = start().await;
+        let client = connect(addr).await;
+        let missing = missing_id().await;
+
+        assert!(matches!(
+            client.get(missing).await,
+            Err(RemoteError::Client(ClientError::NotFound(id))) if id == missing
+        ));
+    }
+
+    /// An id that doesn't exist in a freshly started store.
+    async fn missing_id() -> TicketId {
+        let (client, _server) = launch(5);
+        let admin = User::new("admin", Role::Admin);
+        let project = Project::new(core(), "Core");
+        client.add_project(&admin, project).await.unwrap();
+        client.insert(&admin, draft()).await.unwrap();
+        client.insert(&admin, draft()).await.unwrap().id
+    }
+
+    #[tokio::test]
+    async fn malformed_requests_are_rejected_without_closing_the_connection() {
+        let addr = start().await;
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        socket
+            .write_all(
+                b"{\"command\": \"login\", \"username\": \"admin\", \"password\": \"secret\"}\n",
+            )
+            .await
+            .unwrap();
+        socket
+            .write_all(b"{\"command\": \"insert\", \"draft\": {\"title\": \"\"}}\n")
+            .await
+            .unwrap();
+        socket
+            .write_all(b"{\"command\": \"get\", \"id\": 0}\n")
+            .await
+            .unwrap();
+
+        let mut lines = BufReader::new(socket).lines();
+        let login: Response =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert!(matches!(login, Response::LoggedIn { .. }));
+        let first: Response =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert!(matches!(first, Response::BadRequest { .. }));
+        let second: Response =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert!(matches!(
+            second,
+            Response::Failed {
+                error: ClientError::NotFound(_)
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn overlong_requests_close_the_connection() {
+        let addr = start().await;
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        // Nothing in there is a line ending.
+        let request = vec![b' '; MAX_LINE_LENGTH + 1];
+        socket.write_all(&request).await.unwrap();
+
+        let mut lines = BufReader::new(socket).lines();
+        let response: Response =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert!(matches!(response, Response::BadRequest { .. }));
+        assert_eq!(lines.next_line().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn requests_up_to_the_limit_are_accepted() {
+        let addr = start().await;
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        let login = b"{\"command\": \"login\", \"username\": \"admin\", \"password\": \"secret\"}";
+        // Padded with whitespace, which JSON ignores.
+        let mut request = login.to_vec();
+        request.resize(MAX_LINE_LENGTH, b' ');
+        request.push(b'\n');
+        socket.write_all(&request).await.unwrap();
+
+        let mut lines = BufReader::new(socket).lines();
+        let response: Response =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert!(matches!(response, Response::LoggedIn { .. }));
+    }
+}
@@ -0,0 +1,267 @@
+use crate::clock::{Clock, SystemClock};
+use crate::custom_fields::{CustomFieldError, CustomFields};
+use crate::data::{Status, Ticket, TicketDraft, TicketFilter, TicketPatch, Transition};
+use crate::id::IdGenerator;
+use crate::project::{Project, ProjectKey, TicketKey};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use ticket_fields::TicketTitle;
+
+pub use crate::id::TicketId;
+
+#[derive(Clone)]
+pub struct TicketStore {
+    tickets: BTreeMap<TicketId, Ticket>,
+    keys: BTreeMap<TicketKey, TicketId>,
+    projects: BTreeMap<ProjectKey, ProjectEntry>,
+    ids: IdGenerator,
+    /// Timestamps every change.
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for TicketStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone)]
+struct ProjectEntry {
+    project: Project,
+    /// The number of the last ticket created in the project, removed or not.
+    last_number: u64,
+}
+
+/// A change the store refused to make.
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error, Serialize, Deserialize)]
+pub enum StoreError {
+    #[error("There is no ticket with id {0}")]
+    NotFound(TicketId),
+    #[error("There is no ticket with key {0}")]
+    UnknownTicket(TicketKey),
+    #[error("There is no project with key {0}")]
+    UnknownProject(ProjectKey),
+    #[error("There is already a project with key {0}")]
+    ProjectExists(ProjectKey),
+    #[error("{project} doesn't allow moving tickets from {from:?} to {to:?}")]
+    TransitionNotAllowed {
+        project: ProjectKey,
+        from: Status,
+        to: Status,
+    },
+    #[error("{project} requires an assignee for tickets in {status:?}")]
+    AssigneeRequired { project: ProjectKey, status: Status },
+    #[error("{project} doesn't allow titles longer than {max} bytes")]
+    TitleTooLong { project: ProjectKey, max: usize },
+    #[error(transparent)]
+    CustomField(#[from] CustomFieldError),
+}
+
+impl TicketStore {
+    pub fn new() -> Self {
+        Self {
+            tickets: BTreeMap::new(),
+            keys: BTreeMap::new(),
+            projects: BTreeMap::new(),
+            ids: IdGenerator::default(),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Use `ids` for the tickets created from now on, instead of sequential ids.
+    pub fn with_id_generator(self, ids: IdGenerator) -> Self {
+        Self { ids, ..self }
+    }
+
+    /// Timestamp changes with `clock` instead of the system clock.
+    pub fn with_clock(self, clock: impl Clock + 'static) -> Self {
+        Self {
+            clock: Arc::new(clock),
+            ..self
+        }
+    }
+
+    pub fn add_project(&mut self, project: Project) -> Result<(), StoreError> {
+        if self.projects.contains_key(&project.key) {
+            return Err(StoreError::ProjectExists(project.key));
+        }
+        let entry = ProjectEntry {
+            project,
+            last_number: 0,
+        };
+        self.projects.insert(entry.project.key.clone(), entry);
+        Ok(())
+    }
+
+    /// Every project, in key order.
+    pub fn projects(&self) -> impl Iterator<Item = &Project> {
+        self.projects.values().map(|entry| &entry.project)
+    }
+
+    /// Create a ticket in `ticket.project`, numbered after the project's previous ticket,
+    /// returning it.
+    pub fn add_ticket(
+        &mut self,
+        ticket: TicketDraft,
+        reporter: String,
+    ) -> Result<&Ticket, StoreError> {
+        let entry = self
+            .projects
+            .get_mut(&ticket.project)
+            .ok_or_else(|| StoreError::UnknownProject(ticket.project.clone()))?;
+        let custom_fields = check_custom_fields(
+            &entry.project,
+            ticket.ticket_type.as_deref(),
+            ticket.custom_fields,
+        )?;
+        // Checked before drawing an id, so that rejected drafts don't use one up.
+        check_policy(&entry.project, Status::ToDo, None, &ticket.title)?;
+        let id = self.ids.next_id();
+        let now = self.clock.now();
+        let key = TicketKey {
+            project: ticket.project,
+            number: entry.last_number + 1,
+        };
+        let ticket = Ticket {
+            id,
+            key: key.clone(),
+            title: ticket.title,
+            description: ticket.description,
+            status: Status::ToDo,
+            reporter,
+            assignee: None,
+            ticket_type: ticket.ticket_type,
+            custom_fields,
+            created_at: now,
+            updated_at: now,
+            closed_at: None,
+            history: vec![Transition {
+                status: Status::ToDo,
+                at: now,
+            }],
+        };
+        entry.last_number += 1;
+        self.keys.insert(key, id);
+        let previous = self.tickets.insert(id, ticket);
+        assert!(previous.is_none(), "Ticket id {id} was generated twice");
+        Ok(&self.tickets[&id])
+    }
+
+    pub fn get(&self, id: TicketId) -> Option<&Ticket> {
+        self.tickets.get(&id)
+    }
+
+    pub fn find(&self, key: &TicketKey) -> Option<&Ticket> {
+        self.get(*self.keys.get(key)?)
+    }
+
+    /// Apply `patch`, returning the updated ticket.
+    /// Nothing changes if the result breaks the rules of the ticket's project.
+    pub fn update(&mut self, patch: TicketPatch) -> Result<&Ticket, StoreError> {
+        let now = self.clock.now();
+        let ticket = self
+            .tickets
+            .get_mut(&patch.id)
+            .ok_or(StoreError::NotFound(patch.id))?;
+        let project = &self.projects[&ticket.key.project].project;
+        let mut updated = ticket.clone();
+        if let Some(title) = patch.title {
+            updated.title = title;
+        }
+        if let Some(description) = patch.description {
+            updated.description = description;
+        }
+        if let Some(status) = patch.status {
+            if !project.workflow.allows(ticket.status, status) {
+                return Err(StoreError::TransitionNotAllowed {
+                    project: project.key.clone(),
+                    from: ticket.status,
+                    to: status,
+                });
+            }
+            if status != ticket.status {
+                updated.closed_at = (status == Status::Done).then_some(now);
+                updated.history.push(Transition { status, at: now });
+            }
+            updated.status = status;
+        }
+        if let Some(assignee) = patch.assignee {
+            updated.assignee = Some(assignee);
+        }
+        if !patch.custom_fields.is_empty() {
+            updated.custom_fields = check_custom_fields(
+                project,
+                ticket.ticket_type.as_deref(),
+                ticket.custom_fields.merged(patch.custom_fields),
+            )?;
+        }
+        check_policy(
+            project,
+            updated.status,
+            updated.assignee.as_deref(),
+            &updated.title,
+        )?;
+        updated.updated_at = now;
+        *ticket = updated;
+        Ok(ticket)
+    }
+
+    pub fn remove(&mut self, id: TicketId) -> Option<Ticket> {
+        let ticket = self.tickets.remove(&id)?;
+        self.keys.remove(&ticket.key);
+        Some(ticket)
+    }
+
+    /// The tickets matching `filter`, in id order.
+    pub fn list<'a>(&'a self, filter: &'a TicketFilter) -> impl Iterator<Item = &'a Ticket> {
+        self.tickets
+            .values()
+            .filter(move |ticket| filter.matches(ticket))
+    }
+}
+
+/// Check the fields of a ticket against the field policy of `project`.
+fn check_policy(
+    project: &Project,
+    status: Status,
+    assignee: Option<&str>,
+    title: &TicketTitle,
+) -> Result<(), StoreError> {
+    let policy = &project.field_policy;
+    if policy.assignee_required.contains(&status) && assignee.is_none() {
+        return Err(StoreError::AssigneeRequired {
+            project: project.key.clone(),
+            status,
+        });
+    }
+    if let Some(max) = policy.max_title_length {
+        if title.as_str().len() > max {
+            return Err(StoreError::TitleTooLong {
+                project: project.key.clone(),
+                max,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Check `fields` against the definitions of `ticket_type` in `project`.
+fn check_custom_fields(
+    project: &Project,
+    ticket_type: Option<&str>,
+    fields: CustomFields,
+) -> Result<CustomFields, CustomFieldError> {
+    match ticket_type {
+        Some(name) => project
+            .ticket_type(name)
+            .ok_or_else(|| CustomFieldError::UnknownType(name.to_string()))?
+            .check(fields),
+        None => {
+            if let Some((field, _)) = fields.iter().next() {
+                return Err(CustomFieldError::UnknownField(field.to_string()));
+            }
+            Ok(fields)
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Who is using the store, and what they're allowed to do.
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::Ticket;
+use crate::ClientError;
+
+/// Each role may do everything the previous one can, and more.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Can read tickets.
+    Viewer,
+    /// Can also create tickets, and edit the title and description of those they reported.
+    Reporter,
+    /// Can also edit, assign and move any ticket, and close those assigned to them.
+    Developer,
+    /// Can also close any ticket, remove tickets, read the audit log and manage projects.
+    Admin,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct User {
+    pub name: String,
+    pub role: Role,
+}
+
+/// Something a user may or may not be allowed to do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Read,
+    Create,
+    /// Change the title or the description of a ticket.
+    Edit,
+    Assign,
+    /// Change the status of a ticket to anything but [`Status::Done`](crate::data::Status::Done).
+    Move,
+    /// Move a ticket to [`Status::Done`](crate::data::Status::Done).
+    Close,
+    Remove,
+    ReadAuditLog,
+    ManageProjects,
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Action::Read => "read tickets",
+            Action::Create => "create tickets",
+            Action::Edit => "edit this ticket",
+            Action::Assign => "assign this ticket",
+            Action::Move => "change the status of this ticket",
+            Action::Close => "close this ticket",
+            Action::Remove => "remove tickets",
+            Action::ReadAuditLog => "read the audit log",
+            Action::ManageProjects => "manage projects",
+        })
+    }
+}
+
+impl User {
+    pub fn new(name: impl Into<String>, role: Role) -> Self {
+        Self {
+            name: name.into(),
+            role,
+        }
+    }
+
+    /// Whether the user may perform `action`.
+    /// `ticket` is the ticket it applies to, for actions on an existing ticket.
+    pub fn may(&self, action: Action, ticket: Option<&Ticket>) -> bool {
+        let reported = ticket.is_some_and(|ticket| ticket.reporter == self.name);
+        let assigned = ticket.is_some_and(|ticket| ticket.assignee.as_ref() == Some(&self.name));
+        match action {
+            Action::Read => true,
+            Action::Create => self.role >= Role::Reporter,
+            Action::Edit => {
+                self.role >= Role::Developer || (self.role == Role::Reporter && reported)
+            }
+            Action::Assign | Action::Move => self.role >= Role::Developer,
+            Action::Close => self.role == Role::Admin || (self.role == Role::Developer && assigned),
+            Action::Remove | Action::ReadAuditLog | Action::ManageProjects => {
+                self.role == Role::Admin
+            }
+        }
+    }
+
+    /// Like [`may`](Self::may), failing with [`ClientError::Forbidden`].
+    pub(crate) fn authorize(
+        &self,
+        action: Action,
+        ticket: Option<&Ticket>,
+    ) -> Result<(), ClientError> {
+        if self.may(action, ticket) {
+            Ok(())
+        } else {
+            Err(ClientError::Forbidden {
+                user: self.name.clone(),
+                action,
+            })
+        }
+    }
+}
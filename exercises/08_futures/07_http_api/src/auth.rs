@@ -0,0 +1,51 @@
+use axum::extract::{FromRef, FromRequestParts};
+use axum::http::header;
+use axum::http::request::Parts;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use ticket_server::auth::AuthError;
+use ticket_server::users::User;
+
+use crate::{ApiError, AppState};
+
+/// The user making the request, authenticated with HTTP Basic authentication.
+///
+/// Credentials are checked on every request: only serve the API over HTTPS.
+pub struct Authenticated(pub User);
+
+impl<S> FromRequestParts<S> for Authenticated
+where
+    S: Send + Sync,
+    AppState: FromRef<S>,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let (username, password) = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(basic_credentials)
+            .ok_or_else(|| ApiError::Unauthorized("Missing credentials".into()))?;
+        let authenticator = AppState::from_ref(state).authenticator;
+        let user =
+            tokio::task::spawn_blocking(move || authenticator.authenticate(&username, &password))
+                .await
+                .map_err(|e| ApiError::Internal(e.to_string()))?
+                .map_err(|e| match e {
+                    AuthError::InvalidCredentials => ApiError::Unauthorized(e.to_string()),
+                    // The credentials may well be right: the failure is ours.
+                    AuthError::UserExists(_) | AuthError::Hashing(_) => {
+                        ApiError::Internal(e.to_string())
+                    }
+                })?;
+        Ok(Authenticated(user))
+    }
+}
+
+/// The username and password in an `Authorization: Basic ...` header.
+fn basic_credentials(header: &str) -> Option<(String, String)> {
+    let encoded = header.strip_prefix("Basic ")?;
+    let decoded = String::from_utf8(BASE64_STANDARD.decode(encoded).ok()?).ok()?;
+    let (username, password) = decoded.split_once(':')?;
+    Some((username.to_string(), password.to_string()))
+}
@@ -0,0 +1,115 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Serialize;
+use serde_json::json;
+use ticket_server::store::StoreError;
+use ticket_server::ClientError;
+
+/// How long clients are asked to wait before retrying, when the store is overloaded.
+pub const RETRY_AFTER_SECONDS: u64 = 1;
+
+/// Validation errors, keyed by the name of the offending field.
+#[derive(Debug, Default, Serialize)]
+pub struct FieldErrors(BTreeMap<String, String>);
+
+impl FieldErrors {
+    /// Record `error` against `field`.
+    pub(crate) fn add(&mut self, field: impl Into<String>, error: impl Display) {
+        self.0.insert(field.into(), error.to_string());
+    }
+
+    /// Record the error in `result`, if any, against `field`.
+    pub(crate) fn check<T, E: Display>(
+        &mut self,
+        field: &'static str,
+        result: Result<T, E>,
+    ) -> Option<T> {
+        result.map_err(|error| self.add(field, error)).ok()
+    }
+
+    /// Fail with every error recorded so far, if there is any.
+    pub(crate) fn into_result(self) -> Result<(), ApiError> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(ApiError::Validation(self))
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ApiError {
+    /// Maps to `422 Unprocessable Entity`, listing the errors of each field.
+    /// Bodies and query strings that can't be parsed are reported this way too.
+    Validation(FieldErrors),
+    /// Maps to `401 Unauthorized`, asking for Basic authentication.
+    Unauthorized(String),
+    /// Maps to `404 Not Found` for missing tickets, `403 Forbidden` for denied requests,
+    /// `409 Conflict` for duplicate projects, `422 Unprocessable Entity` for tickets in
+    /// unknown projects and changes breaking a project's rules, `503 Service Unavailable`
+    /// otherwise. Overloaded stores come with a `Retry-After` header.
+    Client(ClientError),
+    /// Maps to `500 Internal Server Error`: something went wrong on our side,
+    /// e.g. a blocking task panicked or a stored password hash is unreadable.
+    Internal(String),
+}
+
+impl From<ClientError> for ApiError {
+    fn from(error: ClientError) -> Self {
+        ApiError::Client(error)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::Validation(errors) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(json!({ "error": "Invalid request", "fields": errors })),
+            )
+                .into_response(),
+            ApiError::Unauthorized(reason) => (
+                StatusCode::UNAUTHORIZED,
+                [(header::WWW_AUTHENTICATE, "Basic realm=\"tickets\"")],
+                Json(json!({ "error": reason })),
+            )
+                .into_response(),
+            ApiError::Client(error) => {
+                let body = Json(json!({ "error": error.to_string() }));
+                match error {
+                    ClientError::NotFound(_)
+                    | ClientError::Rejected(StoreError::UnknownTicket(_)) => {
+                        (StatusCode::NOT_FOUND, body).into_response()
+                    }
+                    ClientError::Rejected(StoreError::ProjectExists(_)) => {
+                        (StatusCode::CONFLICT, body).into_response()
+                    }
+                    // Including unknown projects: the request names them in its body,
+                    // not in its path.
+                    ClientError::Rejected(_) => {
+                        (StatusCode::UNPROCESSABLE_ENTITY, body).into_response()
+                    }
+                    ClientError::Forbidden { .. } => (StatusCode::FORBIDDEN, body).into_response(),
+                    ClientError::Overloaded => (
+                        StatusCode::SERVICE_UNAVAILABLE,
+                        [(header::RETRY_AFTER, RETRY_AFTER_SECONDS.to_string())],
+                        body,
+                    )
+                        .into_response(),
+                    ClientError::Disconnected => {
+                        (StatusCode::SERVICE_UNAVAILABLE, body).into_response()
+                    }
+                }
+            }
+            ApiError::Internal(reason) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({ "error": reason })),
+            )
+                .into_response(),
+        }
+    }
+}
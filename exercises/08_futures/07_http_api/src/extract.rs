@@ -0,0 +1,82 @@
+//! Axum's [`Json`] and [`Query`] extractors, with their rejections reported as
+//! [`ApiError::Validation`], like every other invalid request.
+use std::error::Error;
+
+use axum::extract::rejection::{JsonRejection, QueryRejection};
+use axum::extract::{FromRequest, FromRequestParts, Query, Request};
+use axum::http::request::Parts;
+use axum::Json;
+use serde::de::DeserializeOwned;
+
+use crate::{ApiError, FieldErrors};
+
+/// A JSON request body.
+pub(crate) struct JsonBody<T>(pub T);
+
+impl<T, S> FromRequest<S> for JsonBody<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        match Json::from_request(request, state).await {
+            Ok(Json(value)) => Ok(JsonBody(value)),
+            Err(rejection) => Err(rejected("body", JsonRejection::body_text, rejection)),
+        }
+    }
+}
+
+/// The parameters in the query string.
+pub(crate) struct QueryParams<T>(pub T);
+
+impl<T, S> FromRequestParts<S> for QueryParams<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match Query::from_request_parts(parts, state).await {
+            Ok(Query(value)) => Ok(QueryParams(value)),
+            Err(rejection) => Err(rejected("query", QueryRejection::body_text, rejection)),
+        }
+    }
+}
+
+/// Report `rejection` against the field it's about, or against `whole` if it isn't
+/// about any field in particular, e.g. because the JSON is malformed.
+fn rejected<R: Error + 'static>(
+    whole: &'static str,
+    body_text: impl FnOnce(&R) -> String,
+    rejection: R,
+) -> ApiError {
+    let mut errors = FieldErrors::default();
+    match field_error(&rejection) {
+        // Errors such as missing fields are about the root, whose path is `.`.
+        Some((path, error)) if path == "." => errors.add(whole, error),
+        Some((path, error)) => errors.add(path, error),
+        None => errors.add(whole, body_text(&rejection)),
+    }
+    ApiError::Validation(errors)
+}
+
+/// The path to the value that couldn't be deserialized, and why, if the rejection
+/// comes from deserializing the request.
+fn field_error(rejection: &(dyn Error + 'static)) -> Option<(String, String)> {
+    let mut source = rejection.source();
+    while let Some(error) = source {
+        if let Some(error) = error.downcast_ref::<serde_path_to_error::Error<serde_json::Error>>() {
+            return Some((error.path().to_string(), error.inner().to_string()));
+        }
+        if let Some(error) =
+            error.downcast_ref::<serde_path_to_error::Error<serde::de::value::Error>>()
+        {
+            return Some((error.path().to_string(), error.inner().to_string()));
+        }
+        source = error.source();
+    }
+    None
+}
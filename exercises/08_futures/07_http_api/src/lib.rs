@@ -0,0 +1,568 @@
+//! A REST API for the ticket store, on top of the async client from `06_ticket_server`.
+//!
+//! | Method   | Path                    | Body                        | Success         |
+//! |----------|-------------------------|-----------------------------|-----------------|
+//! | `POST`   | `/tickets`              | [`NewTicket`]               | `201`, ticket   |
+//! | `GET`    | `/tickets`              | [`ListQuery`] as parameters | `200`, tickets  |
+//! | `GET`    | `/tickets/{id}`         |                             | `200`, ticket   |
+//! | `GET`    | `/tickets/by-key/{key}` |                             | `200`, ticket   |
+//! | `PATCH`  | `/tickets/{id}`         | [`TicketChanges`]           | `200`, ticket   |
+//! | `DELETE` | `/tickets/{id}`         |                             | `204`           |
+//! | `POST`   | `/projects`             | [`Project`]                 | `201`, project  |
+//! | `GET`    | `/projects`             |                             | `200`, projects |
+//! | `GET`    | `/audit`                |                             | `200`, entries  |
+//!
+//! Tickets can be found by key, e.g. `/tickets/by-key/CORE-42`.
+//!
+//! Every request must carry the credentials of a user, using HTTP Basic authentication,
+//! and is subject to that user's permissions.
+//! See [`ApiError`] for how failures are reported.
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use axum::extract::{FromRef, Path, State};
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Deserialize;
+use ticket_fields::{TicketDescription, TicketTitle};
+use ticket_server::audit::AuditEntry;
+use ticket_server::auth::Authenticator;
+use ticket_server::custom_fields::{CustomFieldChanges, CustomFieldError, CustomFields};
+use ticket_server::data::{Status, Ticket, TicketDraft, TicketFilter, TicketPatch};
+use ticket_server::project::{Project, ProjectKey, TicketKey};
+use ticket_server::store::TicketId;
+use ticket_server::TicketStoreClient;
+use tokio::net::TcpListener;
+
+pub use crate::auth::Authenticated;
+pub use crate::error::{ApiError, FieldErrors, RETRY_AFTER_SECONDS};
+use crate::extract::{JsonBody, QueryParams};
+
+mod auth;
+mod error;
+mod extract;
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct NewTicket {
+    pub project: String,
+    pub title: String,
+    pub description: String,
+    #[serde(default)]
+    pub ticket_type: Option<String>,
+    #[serde(default)]
+    pub custom_fields: CustomFields,
+}
+
+/// The fields to change, following [`TicketPatch`]: missing fields are left untouched.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct TicketChanges {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub status: Option<Status>,
+    pub assignee: Option<String>,
+    /// Values to add or replace, or `null` to remove a value.
+    /// Fields that aren't listed keep their value.
+    #[serde(default)]
+    pub custom_fields: CustomFieldChanges,
+}
+
+/// Every other parameter is a custom field the tickets must have, e.g. `?severity=high`.
+/// Each of them must be defined by a ticket type the listed tickets can have.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ListQuery {
+    pub project: Option<ProjectKey>,
+    pub status: Option<Status>,
+    pub ticket_type: Option<String>,
+    #[serde(flatten)]
+    pub custom_fields: CustomFields,
+}
+
+#[derive(Clone)]
+pub struct AppState {
+    pub client: TicketStoreClient,
+    pub authenticator: Arc<dyn Authenticator>,
+}
+
+impl FromRef<AppState> for TicketStoreClient {
+    fn from_ref(state: &AppState) -> Self {
+        state.client.clone()
+    }
+}
+
+pub fn router(client: TicketStoreClient, authenticator: Arc<dyn Authenticator>) -> Router {
+    Router::new()
+        .route("/tickets", get(list).post(create))
+        .route("/tickets/{id}", get(show).patch(update).delete(remove))
+        .route("/tickets/by-key/{key}", get(find))
+        .route("/projects", get(projects).post(add_project))
+        .route("/audit", get(audit_log))
+        .with_state(AppState {
+            client,
+            authenticator,
+        })
+}
+
+/// Serve the API on `listener` until an I/O error occurs.
+pub async fn serve(
+    listener: TcpListener,
+    client: TicketStoreClient,
+    authenticator: Arc<dyn Authenticator>,
+) -> std::io::Result<()> {
+    axum::serve(listener, router(client, authenticator)).await
+}
+
+async fn create(
+    State(client): State<TicketStoreClient>,
+    Authenticated(user): Authenticated,
+    JsonBody(ticket): JsonBody<NewTicket>,
+) -> Result<(StatusCode, Json<Ticket>), ApiError> {
+    let mut errors = FieldErrors::default();
+    let project = errors.check("project", ProjectKey::try_from(ticket.project));
+    let title = errors.check("title", TicketTitle::try_from(ticket.title));
+    let description = errors.check(
+        "description",
+        TicketDescription::try_from(ticket.description),
+    );
+    let (Some(project), Some(title), Some(description)) = (project, title, description) else {
+        return Err(ApiError::Validation(errors));
+    };
+    let draft = TicketDraft {
+        project,
+        title,
+        description,
+        ticket_type: ticket.ticket_type,
+        custom_fields: ticket.custom_fields,
+    };
+    let ticket = client.insert(&user, draft).await?;
+    Ok((StatusCode::CREATED, Json(ticket)))
+}
+
+async fn list(
+    State(client): State<TicketStoreClient>,
+    Authenticated(user): Authenticated,
+    QueryParams(query): QueryParams<ListQuery>,
+) -> Result<Json<Vec<Ticket>>, ApiError> {
+    if !query.custom_fields.is_empty() {
+        let projects = client.projects(&user).await?;
+        check_filtered_fields(&query, &projects)?;
+    }
+    let filter = TicketFilter {
+        project: query.project,
+        status: query.status,
+        ticket_type: query.ticket_type,
+        custom_fields: query.custom_fields,
+    };
+    Ok(Json(client.list(&user, filter).await?))
+}
+
+/// Fail unless every custom field in `query` is defined by one of the ticket types
+/// it can match: a filter on any other field would just match nothing.
+fn check_filtered_fields(query: &ListQuery, projects: &[Project]) -> Result<(), ApiError> {
+    let defined: BTreeSet<&str> = projects
+        .iter()
+        .filter(|project| query.project.as_ref().is_none_or(|key| project.key == *key))
+        .flat_map(|project| &project.ticket_types)
+        .filter(|ticket_type| {
+            query
+                .ticket_type
+                .as_ref()
+                .is_none_or(|name| ticket_type.name == *name)
+        })
+        .flat_map(|ticket_type| &ticket_type.fields)
+        .map(|field| field.name.as_str())
+        .collect();
+    let mut errors = FieldErrors::default();
+    for (field, _) in query.custom_fields.iter() {
+        if !defined.contains(field) {
+            errors.add(field, CustomFieldError::UnknownField(field.to_string()));
+        }
+    }
+    errors.into_result()
+}
+
+async fn show(
+    State(client): State<TicketStoreClient>,
+    Authenticated(user): Authenticated,
+    Path(id): Path<TicketId>,
+) -> Result<Json<Ticket>, ApiError> {
+    Ok(Json(client.get(&user, id).await?))
+}
+
+async fn find(
+    State(client): State<TicketStoreClient>,
+    Authenticated(user): Authenticated,
+    Path(key): Path<TicketKey>,
+) -> Result<Json<Ticket>, ApiError> {
+    Ok(Json(client.find(&user, key).await?))
+}
+
+async fn update(
+    State(client): State<TicketStoreClient>,
+    Authenticated(user): Authenticated,
+    Path(id): Path<TicketId>,
+    JsonBody(changes): JsonBody<TicketChanges>,
+) -> Result<Json<Ticket>, ApiError> {
+    let mut errors = FieldErrors::default();
+    let title = errors.check(
+        "title",
+        changes.title.map(TicketTitle::try_from).transpose(),
+    );
+    let description = errors.check(
+        "description",
+        changes
+            .description
+            .map(TicketDescription::try_from)
+            .transpose(),
+    );
+    errors.into_result()?;
+    let patch = TicketPatch {
+        id,
+        title: title.flatten(),
+        description: description.flatten(),
+        status: changes.status,
+        assignee: changes.assignee,
+        custom_fields: changes.custom_fields,
+    };
+    Ok(Json(client.update(&user, patch).await?))
+}
+
+async fn remove(
+    State(client): State<TicketStoreClient>,
+    Authenticated(user): Authenticated,
+    Path(id): Path<TicketId>,
+) -> Result<StatusCode, ApiError> {
+    client.remove(&user, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn add_project(
+    State(client): State<TicketStoreClient>,
+    Authenticated(user): Authenticated,
+    JsonBody(project): JsonBody<Project>,
+) -> Result<(StatusCode, Json<Project>), ApiError> {
+    client.add_project(&user, project.clone()).await?;
+    Ok((StatusCode::CREATED, Json(project)))
+}
+
+async fn projects(
+    State(client): State<TicketStoreClient>,
+    Authenticated(user): Authenticated,
+) -> Result<Json<Vec<Project>>, ApiError> {
+    Ok(Json(client.projects(&user).await?))
+}
+
+async fn audit_log(
+    State(client): State<TicketStoreClient>,
+    Authenticated(user): Authenticated,
+) -> Result<Json<Vec<AuditEntry>>, ApiError> {
+    Ok(Json(client.audit_log(&user).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{header, Request};
+    use axum::response::{IntoResponse, Response};
+    use base64::prelude::{Engine, BASE64_STANDARD};
+    use http_body_util::BodyExt;
+    use serde_json::{json, Value};
+    use ticket_server::auth::{AuthError, LocalAuthenticator};
+    use ticket_server::users::{Role, User};
+    use tower::ServiceExt;
+
+    async fn app() -> Router {
+        let (client, _server) = ticket_server::launch(16);
+        let admin = User::new("admin", Role::Admin);
+        let project = Project::new(ProjectKey::try_from("CORE").unwrap(), "Core");
+        client.add_project(&admin, project).await.unwrap();
+        let mut authenticator = LocalAuthenticator::new();
+        authenticator
+            .add_user("admin", "secret", Role::Admin)
+            .unwrap();
+        authenticator
+            .add_user("viewer", "secret", Role::Viewer)
+            .unwrap();
+        router(client, Arc::new(authenticator))
+    }
+
+    /// Send a request on behalf of the admin.
+    async fn send(router: &Router, method: &str, uri: &str, body: Option<Value>) -> Response {
+        send_as(router, Some("admin:secret"), method, uri, body).await
+    }
+
+    async fn send_as(
+        router: &Router,
+        credentials: Option<&str>,
+        method: &str,
+        uri: &str,
+        body: Option<Value>,
+    ) -> Response {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(credentials) = credentials {
+            let encoded = BASE64_STANDARD.encode(credentials);
+            request = request.header(header::AUTHORIZATION, format!("Basic {encoded}"));
+        }
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        };
+        router.clone().oneshot(request.unwrap()).await.unwrap()
+    }
+
+    async fn json_body(response: Response) -> Value {
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    fn new_ticket() -> Value {
+        json!({ "project": "CORE", "title": "A title", "description": "A description" })
+    }
+
+    #[tokio::test]
+    async fn ticket_lifecycle() {
+        let router = app().await;
+
+        let response = send(&router, "POST", "/tickets", Some(new_ticket())).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let ticket = json_body(response).await;
+        assert_eq!(ticket["status"], "ToDo");
+        assert_eq!(ticket["key"], "CORE-1");
+        let uri = format!("/tickets/{}", ticket["id"].as_str().unwrap());
+        let response = send(&router, "GET", "/tickets/by-key/CORE-1", None).await;
+        assert_eq!(json_body(response).await, ticket);
+
+        let response = send(&router, "PATCH", &uri, Some(json!({ "status": "Done" }))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let updated = json_body(response).await;
+        assert_eq!(updated["status"], "Done");
+        assert_eq!(updated["title"], "A title");
+
+        let response = send(&router, "GET", "/tickets?status=Done", None).await;
+        assert_eq!(json_body(response).await, json!([updated]));
+        let response = send(&router, "GET", "/tickets?status=ToDo", None).await;
+        assert_eq!(json_body(response).await, json!([]));
+        let response = send(&router, "GET", "/tickets?project=WEB", None).await;
+        assert_eq!(json_body(response).await, json!([]));
+
+        let response = send(&router, "DELETE", &uri, None).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let response = send(&router, "GET", &uri, None).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        // Adding the project, then creating, closing and removing the ticket.
+        let response = send(&router, "GET", "/audit", None).await;
+        assert_eq!(json_body(response).await.as_array().unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn requests_are_authenticated_and_authorized() {
+        let router = app().await;
+
+        let response = send_as(&router, None, "GET", "/tickets", None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert!(response.headers().contains_key(header::WWW_AUTHENTICATE));
+        let response = send_as(&router, Some("admin:wrong"), "GET", "/tickets", None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let viewer = Some("viewer:secret");
+        let response = send_as(&router, viewer, "GET", "/tickets", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = send_as(&router, viewer, "POST", "/tickets", Some(new_ticket())).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(
+            json_body(response).await["error"],
+            "viewer is not allowed to create tickets"
+        );
+    }
+
+    #[tokio::test]
+    async fn a_failing_authenticator_is_a_server_error() {
+        struct Panicking;
+        impl Authenticator for Panicking {
+            fn authenticate(&self, _: &str, _: &str) -> Result<User, AuthError> {
+                panic!("The user database is gone");
+            }
+        }
+        struct Corrupted;
+        impl Authenticator for Corrupted {
+            fn authenticate(&self, _: &str, _: &str) -> Result<User, AuthError> {
+                Err(AuthError::Hashing(
+                    argon2::password_hash::Error::PhcStringField,
+                ))
+            }
+        }
+        let (client, _server) = ticket_server::launch(16);
+
+        for authenticator in [
+            Arc::new(Panicking) as Arc<dyn Authenticator>,
+            Arc::new(Corrupted),
+        ] {
+            let router = router(client.clone(), authenticator);
+            let response = send(&router, "GET", "/tickets", None).await;
+            assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+            assert!(!response.headers().contains_key(header::WWW_AUTHENTICATE));
+        }
+    }
+
+    #[tokio::test]
+    async fn validation_errors_are_reported_per_field() {
+        let router = app().await;
+
+        let body = json!({ "project": "CORE", "title": "", "description": "x".repeat(501) });
+        let response = send(&router, "POST", "/tickets", Some(body)).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let fields = &json_body(response).await["fields"];
+        assert_eq!(fields["title"], "The title cannot be empty");
+        assert_eq!(
+            fields["description"],
+            "The description cannot be longer than 500 bytes"
+        );
+
+        let ticket = json_body(send(&router, "POST", "/tickets", Some(new_ticket())).await).await;
+        let uri = format!("/tickets/{}", ticket["id"].as_str().unwrap());
+        let response = send(&router, "PATCH", &uri, Some(json!({ "title": "" }))).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let fields = &json_body(response).await["fields"];
+        assert_eq!(fields, &json!({ "title": "The title cannot be empty" }));
+    }
+
+    #[tokio::test]
+    async fn unparseable_requests_are_validation_errors() {
+        let router = app().await;
+
+        let body = json!({ "project": "CORE", "title": "A title" });
+        let response = send(&router, "POST", "/tickets", Some(body)).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let fields = &json_body(response).await["fields"];
+        assert!(fields["body"]
+            .as_str()
+            .unwrap()
+            .starts_with("missing field `description`"));
+
+        let ticket = json_body(send(&router, "POST", "/tickets", Some(new_ticket())).await).await;
+        let uri = format!("/tickets/{}", ticket["id"].as_str().unwrap());
+        let response = send(&router, "PATCH", &uri, Some(json!({ "status": "Closed" }))).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let fields = &json_body(response).await["fields"];
+        assert!(fields["status"]
+            .as_str()
+            .unwrap()
+            .starts_with("unknown variant `Closed`"));
+
+        let response = send(&router, "GET", "/tickets?status=Closed", None).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let fields = &json_body(response).await["fields"];
+        assert!(fields["status"]
+            .as_str()
+            .unwrap()
+            .starts_with("unknown variant `Closed`"));
+    }
+
+    #[test]
+    fn an_overloaded_store_asks_clients_to_retry() {
+        let response = ApiError::Client(ticket_server::ClientError::Overloaded).into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            response.headers()[header::RETRY_AFTER],
+            RETRY_AFTER_SECONDS.to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn projects_can_be_added_and_listed() {
+        let router = app().await;
+        let web = json!({ "key": "WEB", "name": "Website" });
+        let response = send(&router, "POST", "/projects", Some(web.clone())).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let response = send(&router, "POST", "/projects", Some(web)).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let projects = json_body(send(&router, "GET", "/projects", None).await).await;
+        let keys: Vec<_> = projects
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|project| project["key"].as_str().unwrap())
+            .collect();
+        assert_eq!(keys, ["CORE", "WEB"]);
+
+        let body = json!({ "project": "NOPE", "title": "A title", "description": "A description" });
+        let response = send(&router, "POST", "/tickets", Some(body)).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn custom_fields_are_validated_and_filtered_on() {
+        let router = app().await;
+        let ops = json!({
+            "key": "OPS",
+            "name": "Operations",
+            "ticket_types": [{
+                "name": "incident",
+                "fields": [
+                    { "name": "severity", "kind": { "type": "enum", "options": ["low", "high"] }, "required": true },
+                    { "name": "due", "kind": { "type": "date" } },
+                ],
+            }],
+        });
+        let response = send(&router, "POST", "/projects", Some(ops)).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let incident = |severity: &str| {
+            json!({
+                "project": "OPS",
+                "title": "A title",
+                "description": "A description",
+                "ticket_type": "incident",
+                "custom_fields": { "severity": severity },
+            })
+        };
+        let response = send(&router, "POST", "/tickets", Some(incident("meh"))).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            json_body(response).await["error"],
+            "The severity field must be one of: low, high"
+        );
+        send(&router, "POST", "/tickets", Some(incident("high"))).await;
+        let ticket =
+            json_body(send(&router, "POST", "/tickets", Some(incident("low"))).await).await;
+
+        let uri = format!("/tickets/{}", ticket["id"].as_str().unwrap());
+        let changes = json!({ "custom_fields": { "due": "2024-05-01" } });
+        let updated = json_body(send(&router, "PATCH", &uri, Some(changes)).await).await;
+        assert_eq!(
+            updated["custom_fields"],
+            json!({ "severity": "low", "due": "2024-05-01" })
+        );
+        let changes = json!({ "custom_fields": { "due": null } });
+        let undated = json_body(send(&router, "PATCH", &uri, Some(changes)).await).await;
+        assert_eq!(undated["custom_fields"], json!({ "severity": "low" }));
+        let changes = json!({ "custom_fields": { "due": "2024-05-01" } });
+        let updated = json_body(send(&router, "PATCH", &uri, Some(changes)).await).await;
+
+        let response = send(
+            &router,
+            "GET",
+            "/tickets?ticket_type=incident&severity=low",
+            None,
+        )
+        .await;
+        assert_eq!(json_body(response).await, json!([updated]));
+        let response = send(&router, "GET", "/tickets?severity=high", None).await;
+        assert_eq!(json_body(response).await.as_array().unwrap().len(), 1);
+
+        let response = send(&router, "GET", "/tickets?severty=high", None).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let fields = &json_body(response).await["fields"];
+        assert_eq!(
+            fields,
+            &json!({ "severty": "There is no custom field named severty" })
+        );
+        let response = send(&router, "GET", "/tickets?project=CORE&severity=high", None).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+}
@@ -1,6 +1,17 @@
 #[derive(Debug, PartialEq, Clone, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String")
+)]
 pub struct TicketDescription(String);
 
+impl TicketDescription {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum TicketDescriptionError {
     #[error("The description cannot be empty")]
@@ -1,8 +1,19 @@
 use std::convert::TryFrom;
 
 #[derive(Debug, PartialEq, Clone, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String")
+)]
 pub struct TicketTitle(String);
 
+impl TicketTitle {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum TicketTitleError {
     #[error("The title cannot be empty")]
@@ -0,0 +1,407 @@
+//! A kanban board for the terminal, with a column per [`Status`].
+//!
+//! [`Board`] holds the state and turns key presses into changes to the store; it draws
+//! itself on any ratatui backend, so it can be tested against a `TestBackend`.
+//! [`run`] wires it to the actual terminal.
+//!
+//! | Key                    | Action                                         |
+//! |------------------------|------------------------------------------------|
+//! | `←` `→` / `h` `l`      | Select the previous/next column                |
+//! | `↑` `↓` / `k` `j`      | Select the previous/next card                  |
+//! | `<` `>`                | Move the selected card to the previous/next column |
+//! | `e` / `Enter`          | Edit the selected card                         |
+//! | `/`                    | Search titles and descriptions                 |
+//! | `Esc`                  | Clear the search                               |
+//! | `q`                    | Quit                                           |
+//!
+//! While editing, `Tab` switches between title and description, `Enter` saves
+//! and `Esc` discards the changes.
+use std::io;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use ticket_fields::{TicketDescription, TicketTitle};
+
+use crate::data::{Status, Ticket, TicketId, TicketPatch};
+use crate::store::TicketStore;
+
+const COLUMNS: [Status; 3] = [Status::ToDo, Status::InProgress, Status::Done];
+
+/// What the caller should do after a key press.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Control {
+    Continue,
+    /// The store was modified and should be saved.
+    Changed,
+    Quit,
+}
+
+pub struct Board {
+    store: TicketStore,
+    column: usize,
+    /// The selected card of each column.
+    rows: [usize; COLUMNS.len()],
+    search: String,
+    mode: Mode,
+}
+
+enum Mode {
+    Browse,
+    Search,
+    Edit(Editor),
+}
+
+struct Editor {
+    id: TicketId,
+    title: String,
+    description: String,
+    field: Field,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Title,
+    Description,
+}
+
+impl Editor {
+    fn title(&self) -> Result<TicketTitle, String> {
+        TicketTitle::try_from(self.title.as_str()).map_err(|e| e.to_string())
+    }
+
+    fn description(&self) -> Result<TicketDescription, String> {
+        TicketDescription::try_from(self.description.as_str()).map_err(|e| e.to_string())
+    }
+
+    fn text(&mut self) -> &mut String {
+        match self.field {
+            Field::Title => &mut self.title,
+            Field::Description => &mut self.description,
+        }
+    }
+}
+
+impl Board {
+    pub fn new(store: TicketStore) -> Self {
+        Self {
+            store,
+            column: 0,
+            rows: [0; COLUMNS.len()],
+            search: String::new(),
+            mode: Mode::Browse,
+        }
+    }
+
+    pub fn store(&self) -> &TicketStore {
+        &self.store
+    }
+
+    /// The ticket under the cursor, if the selected column isn't empty.
+    pub fn selected(&self) -> Option<&Ticket> {
+        self.cards(self.column)
+            .into_iter()
+            .nth(self.rows[self.column])
+    }
+
+    /// The tickets shown in the given column, i.e. those matching the search.
+    fn cards(&self, column: usize) -> Vec<&Ticket> {
+        let search = self.search.to_lowercase();
+        self.store
+            .list(Some(COLUMNS[column]))
+            .filter(|ticket| {
+                ticket.title.as_str().to_lowercase().contains(&search)
+                    || ticket.description.as_str().to_lowercase().contains(&search)
+            })
+            .collect()
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Control {
+        if key.kind != KeyEventKind::Press {
+            return Control::Continue;
+        }
+        let control = match &mut self.mode {
+            Mode::Browse => return self.browse(key.code),
+            Mode::Search => {
+                match key.code {
+                    KeyCode::Char(c) => self.search.push(c),
+                    KeyCode::Backspace => {
+                        self.search.pop();
+                    }
+                    KeyCode::Enter => self.mode = Mode::Browse,
+                    KeyCode::Esc => {
+                        self.search.clear();
+                        self.mode = Mode::Browse;
+                    }
+                    _ => {}
+                }
+                Control::Continue
+            }
+            Mode::Edit(editor) => match key.code {
+                KeyCode::Char(c) => {
+                    editor.text().push(c);
+                    Control::Continue
+                }
+                KeyCode::Backspace => {
+                    editor.text().pop();
+                    Control::Continue
+                }
+                KeyCode::Tab | KeyCode::BackTab => {
+                    editor.field = match editor.field {
+                        Field::Title => Field::Description,
+                        Field::Description => Field::Title,
+                    };
+                    Control::Continue
+                }
+                KeyCode::Esc => {
+                    self.mode = Mode::Browse;
+                    Control::Continue
+                }
+                KeyCode::Enter => {
+                    // Invalid values stay in the editor, next to their error.
+                    let (Ok(title), Ok(description)) = (editor.title(), editor.description())
+                    else {
+                        return Control::Continue;
+                    };
+                    let id = editor.id;
+                    let patch = TicketPatch {
+                        title: Some(title),
+                        description: Some(description),
+                        status: None,
+                    };
+                    self.mode = Mode::Browse;
+                    self.patch(id, patch)
+                }
+                _ => Control::Continue,
+            },
+        };
+        self.clamp_rows();
+        control
+    }
+
+    fn browse(&mut self, code: KeyCode) -> Control {
+        let last_column = COLUMNS.len() - 1;
+        let control = match code {
+            KeyCode::Char('q') => return Control::Quit,
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.column = self.column.saturating_sub(1);
+                Control::Continue
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.column = (self.column + 1).min(last_column);
+                Control::Continue
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.rows[self.column] = self.rows[self.column].saturating_sub(1);
+                Control::Continue
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.rows[self.column] += 1;
+                Control::Continue
+            }
+            KeyCode::Char('<') if self.column > 0 => self.move_selected(self.column - 1),
+            KeyCode::Char('>') if self.column < last_column => self.move_selected(self.column + 1),
+            KeyCode::Char('e') | KeyCode::Enter => {
+                if let Some(ticket) = self.selected() {
+                    self.mode = Mode::Edit(Editor {
+                        id: ticket.id,
+                        title: ticket.title.as_str().to_owned(),
+                        description: ticket.description.as_str().to_owned(),
+                        field: Field::Title,
+                    });
+                }
+                Control::Continue
+            }
+            KeyCode::Char('/') => {
+                self.mode = Mode::Search;
+                Control::Continue
+            }
+            KeyCode::Esc => {
+                self.search.clear();
+                Control::Continue
+            }
+            _ => Control::Continue,
+        };
+        self.clamp_rows();
+        control
+    }
+
+    /// Move the selected card to `column`, keeping it selected.
+    fn move_selected(&mut self, column: usize) -> Control {
+        let Some(id) = self.selected().map(|ticket| ticket.id) else {
+            return Control::Continue;
+        };
+        let patch = TicketPatch {
+            status: Some(COLUMNS[column]),
+            ..TicketPatch::default()
+        };
+        let control = self.patch(id, patch);
+        self.column = column;
+        if let Some(row) = self.cards(column).iter().position(|ticket| ticket.id == id) {
+            self.rows[column] = row;
+        }
+        control
+    }
+
+    fn patch(&mut self, id: TicketId, patch: TicketPatch) -> Control {
+        match self.store.update(id, patch) {
+            Ok(_) => Control::Changed,
+            // The ticket comes from the store we own: it can't have disappeared.
+            Err(_) => Control::Continue,
+        }
+    }
+
+    fn clamp_rows(&mut self) {
+        for column in 0..COLUMNS.len() {
+            let cards = self.cards(column).len();
+            self.rows[column] = self.rows[column].min(cards.saturating_sub(1));
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame) {
+        let [board, footer] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+        let columns = Layout::horizontal([Constraint::Ratio(1, 3); COLUMNS.len()]).split(board);
+        for (index, area) in columns.iter().enumerate() {
+            self.render_column(frame, index, *area);
+        }
+
+        let footer_text = match &self.mode {
+            Mode::Search => format!("Search: {}_", self.search),
+            _ if !self.search.is_empty() => format!("Search: {} (Esc to clear)", self.search),
+            _ => "←→ column  ↑↓ card  <> move  e edit  / search  q quit".to_string(),
+        };
+        frame.render_widget(Paragraph::new(footer_text).dark_gray(), footer);
+
+        if let Mode::Edit(editor) = &self.mode {
+            render_editor(frame, editor);
+        }
+    }
+
+    fn render_column(&self, frame: &mut Frame, index: usize, area: Rect) {
+        let cards = self.cards(index);
+        let title = format!(" {} ({}) ", column_title(COLUMNS[index]), cards.len());
+        let focused = index == self.column;
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(if focused {
+                Style::default().fg(Color::Cyan)
+            } else {
+                Style::default()
+            });
+        let items: Vec<_> = cards
+            .iter()
+            .map(|ticket| ListItem::new(format!("{} {}", ticket.id, ticket.title.as_str())))
+            .collect();
+        let list = List::new(items)
+            .block(block)
+            .highlight_symbol("> ")
+            .highlight_style(Style::default().add_modifier(Modifier::BOLD));
+        let mut state = ListState::default();
+        if focused && !cards.is_empty() {
+            state.select(Some(self.rows[index]));
+        }
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+}
+
+fn column_title(status: Status) -> &'static str {
+    match status {
+        Status::ToDo => "To do",
+        Status::InProgress => "In progress",
+        Status::Done => "Done",
+    }
+}
+
+fn render_editor(frame: &mut Frame, editor: &Editor) {
+    let [area] = Layout::horizontal([Constraint::Percentage(80)])
+        .flex(Flex::Center)
+        .areas(frame.area());
+    let [area] = Layout::vertical([Constraint::Length(12)])
+        .flex(Flex::Center)
+        .areas(area);
+    frame.render_widget(Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" Edit ticket {} ", editor.id))
+        .title_bottom(" Tab switch field  Enter save  Esc cancel ");
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [title, description] =
+        Layout::vertical([Constraint::Length(4), Constraint::Min(4)]).areas(inner);
+    render_field(
+        frame,
+        title,
+        "Title",
+        &editor.title,
+        editor.title().err(),
+        editor.field == Field::Title,
+    );
+    render_field(
+        frame,
+        description,
+        "Description",
+        &editor.description,
+        editor.description().err(),
+        editor.field == Field::Description,
+    );
+}
+
+fn render_field(
+    frame: &mut Frame,
+    area: Rect,
+    label: &str,
+    value: &str,
+    error: Option<String>,
+    focused: bool,
+) {
+    let cursor = if focused { "_" } else { "" };
+    let mut lines = vec![
+        Line::from(label.bold()),
+        Line::from(format!("{value}{cursor}")),
+    ];
+    if let Some(error) = error {
+        lines.push(Line::from(error).red());
+    }
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), area);
+}
+
+/// Show `board` on the terminal until the user quits,
+/// calling `save` with the store after every change.
+pub fn run<E>(
+    board: &mut Board,
+    mut save: impl FnMut(&TicketStore) -> Result<(), E>,
+) -> Result<(), E>
+where
+    E: From<io::Error>,
+{
+    let mut terminal = ratatui::init();
+    let outcome = event_loop(&mut terminal, board, &mut save);
+    ratatui::restore();
+    outcome
+}
+
+fn event_loop<E>(
+    terminal: &mut DefaultTerminal,
+    board: &mut Board,
+    save: &mut impl FnMut(&TicketStore) -> Result<(), E>,
+) -> Result<(), E>
+where
+    E: From<io::Error>,
+{
+    loop {
+        terminal.draw(|frame| board.render(frame))?;
+        if let Event::Key(key) = event::read()? {
+            match board.handle_key(key) {
+                Control::Continue => {}
+                Control::Changed => save(board.store())?,
+                Control::Quit => return Ok(()),
+            }
+        }
+    }
+}
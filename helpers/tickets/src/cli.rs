@@ -0,0 +1,445 @@
+//! The `tickets` command line interface.
+//!
+//! Every command loads the ticket file, applies the change (if any) and saves it back.
+//! Failures are reported with an exit code that tells them apart, see [`CliError::exit_code`].
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use cron::Schedule;
+use serde::Serialize;
+use ticket_fields::{TicketDescription, TicketTitle};
+
+use crate::board::{self, Board};
+use crate::data::{Status, Ticket, TicketDraft, TicketId, TicketPatch};
+use crate::recurring::RecurringRule;
+use crate::store::{StoreError, TicketStore};
+use crate::template::{TemplateError, Templates};
+
+/// Something went wrong that doesn't fall into a more specific category,
+/// e.g. the ticket file couldn't be read.
+pub const EXIT_FAILURE: u8 = 1;
+/// The command line couldn't be parsed. Reported by `clap` itself.
+pub const EXIT_USAGE: u8 = 2;
+/// A value was rejected, e.g. an empty title.
+pub const EXIT_INVALID: u8 = 3;
+/// The requested ticket doesn't exist.
+pub const EXIT_NOT_FOUND: u8 = 4;
+
+/// How timestamps are shown in text output.
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M UTC";
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "tickets",
+    version,
+    about = "Manage the tickets stored in a local file"
+)]
+pub struct Cli {
+    /// The file the tickets are stored in. It's created if it doesn't exist.
+    #[arg(
+        long,
+        global = true,
+        env = "TICKETS_FILE",
+        default_value = "tickets.json"
+    )]
+    pub file: PathBuf,
+    /// The TOML file ticket templates are read from.
+    #[arg(
+        long,
+        global = true,
+        env = "TICKETS_TEMPLATES",
+        default_value = "templates.toml"
+    )]
+    pub templates: PathBuf,
+    /// Print machine-readable JSON instead of text.
+    #[arg(long, global = true)]
+    pub json: bool,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Create a ticket, from scratch or from a template.
+    New {
+        #[arg(
+            long,
+            required_unless_present = "template",
+            conflicts_with = "template"
+        )]
+        title: Option<String>,
+        #[arg(
+            long,
+            required_unless_present = "template",
+            conflicts_with = "template"
+        )]
+        description: Option<String>,
+        /// The name of a template in the templates file.
+        #[arg(long)]
+        template: Option<String>,
+        /// A value for a template variable, e.g. `--var component=parser`.
+        #[arg(long = "var", value_name = "NAME=VALUE", value_parser = parse_variable, requires = "template")]
+        variables: Vec<(String, String)>,
+    },
+    /// Show a ticket, with its comments.
+    Show { id: TicketId },
+    /// List tickets, optionally only those with a given status.
+    List {
+        #[arg(long, value_enum)]
+        status: Option<Status>,
+    },
+    /// Change the title and/or the description of a ticket.
+    #[command(group(ArgGroup::new("changes").required(true).multiple(true)))]
+    Edit {
+        id: TicketId,
+        #[arg(long, group = "changes")]
+        title: Option<String>,
+        #[arg(long, group = "changes")]
+        description: Option<String>,
+    },
+    /// Change the status of a ticket.
+    Move {
+        id: TicketId,
+        #[arg(value_enum)]
+        status: Status,
+    },
+    /// Add a comment to a ticket.
+    Comment { id: TicketId, body: String },
+    /// Browse and edit the tickets on a kanban board.
+    Board,
+    /// Write every ticket to standard output or to a file.
+    Export {
+        #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+        format: ExportFormat,
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Show lead time, cycle time, throughput and work in progress.
+    Report {
+        #[arg(long, value_enum, default_value_t = ReportFormat::Table)]
+        format: ReportFormat,
+    },
+    /// Manage tickets that are created on a schedule.
+    Recur {
+        #[command(subcommand)]
+        command: RecurCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RecurCommand {
+    /// Create a ticket on a schedule, starting with its next occurrence.
+    Add {
+        name: String,
+        /// A cron expression with seconds, in UTC, e.g. `0 0 9 * * Mon`.
+        #[arg(long)]
+        schedule: String,
+        #[arg(long)]
+        title: String,
+        #[arg(long)]
+        description: String,
+    },
+    /// List the recurring tickets, with when each is due next.
+    List,
+    /// Stop creating a recurring ticket. Tickets already created are kept.
+    Remove { name: String },
+    /// Create the tickets that are due. Meant to be run regularly, e.g. from a cron job.
+    Run,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    Table,
+    Csv,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CliError {
+    #[error("Invalid {field}: {message}")]
+    Invalid {
+        field: &'static str,
+        message: String,
+    },
+    #[error(transparent)]
+    Store(#[from] StoreError),
+    #[error(transparent)]
+    Template(#[from] TemplateError),
+    #[error("Could not write the output: {0}")]
+    Output(#[from] io::Error),
+}
+
+impl CliError {
+    /// The code the process should exit with.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            CliError::Invalid { .. } | CliError::Store(StoreError::EmptyComment) => EXIT_INVALID,
+            CliError::Store(StoreError::RuleExists(_)) => EXIT_INVALID,
+            CliError::Store(StoreError::NotFound(_) | StoreError::UnknownRule(_)) => EXIT_NOT_FOUND,
+            CliError::Template(TemplateError::Io(_) | TemplateError::Corrupted(_)) => EXIT_FAILURE,
+            CliError::Template(_) => EXIT_INVALID,
+            CliError::Store(_) | CliError::Output(_) => EXIT_FAILURE,
+        }
+    }
+}
+
+/// Run `cli`, writing its output to `out`.
+pub fn run(cli: Cli, out: &mut impl Write) -> Result<(), CliError> {
+    let mut store = TicketStore::load(&cli.file)?;
+    // What to print, once the store has been saved.
+    let (id, changed) = match cli.command {
+        Command::New {
+            title,
+            description,
+            template,
+            variables,
+        } => {
+            let draft = match (template, title, description) {
+                (Some(template), _, _) => {
+                    let variables: BTreeMap<_, _> = variables.into_iter().collect();
+                    Templates::load(&cli.templates)?
+                        .get(&template)?
+                        .render(&variables)?
+                }
+                (None, Some(title), Some(description)) => TicketDraft {
+                    title: title_from(title)?,
+                    description: description_from(description)?,
+                },
+                // clap requires both when there's no template.
+                (None, _, _) => unreachable!("a new ticket needs a title and a description"),
+            };
+            (store.add_ticket(draft), true)
+        }
+        Command::Show { id } => (id, false),
+        Command::List { status } => {
+            let tickets = store.list(status).collect();
+            Output::Tickets(tickets).print(cli.json, out)?;
+            return Ok(());
+        }
+        Command::Edit {
+            id,
+            title,
+            description,
+        } => {
+            let patch = TicketPatch {
+                title: title.map(title_from).transpose()?,
+                description: description.map(description_from).transpose()?,
+                status: None,
+            };
+            store.update(id, patch)?;
+            (id, true)
+        }
+        Command::Move { id, status } => {
+            let patch = TicketPatch {
+                status: Some(status),
+                ..TicketPatch::default()
+            };
+            store.update(id, patch)?;
+            (id, true)
+        }
+        Command::Comment { id, body } => {
+            store.comment(id, body)?;
+            (id, true)
+        }
+        Command::Board => {
+            let mut board = Board::new(store);
+            return board::run(&mut board, |store| Ok(store.save(&cli.file)?));
+        }
+        Command::Export { format, output } => {
+            let tickets: Vec<_> = store.list(None).collect();
+            match output {
+                Some(path) => export(&tickets, format, &mut std::fs::File::create(path)?)?,
+                None => export(&tickets, format, out)?,
+            }
+            return Ok(());
+        }
+        Command::Recur { command } => {
+            let (output, changed) = match command {
+                RecurCommand::Add {
+                    name,
+                    schedule,
+                    title,
+                    description,
+                } => {
+                    let draft = TicketDraft {
+                        title: title_from(title)?,
+                        description: description_from(description)?,
+                    };
+                    let schedule = schedule_from(&schedule)?;
+                    let rule = store.add_recurring(name, schedule, draft)?.clone();
+                    (Output::Rules(vec![rule]), true)
+                }
+                RecurCommand::List => (Output::Rules(store.recurring().cloned().collect()), false),
+                RecurCommand::Remove { name } => {
+                    (Output::Rules(vec![store.remove_recurring(&name)?]), true)
+                }
+                RecurCommand::Run => {
+                    let created = store.create_due();
+                    let tickets = created
+                        .into_iter()
+                        .map(|id| store.get(id))
+                        .collect::<Result<_, _>>()?;
+                    (Output::Tickets(tickets), true)
+                }
+            };
+            if changed {
+                store.save(&cli.file)?;
+            }
+            output.print(cli.json, out)?;
+            return Ok(());
+        }
+        Command::Report { format } => {
+            let report = store.report();
+            match format {
+                ReportFormat::Table => report.write_table(out)?,
+                ReportFormat::Csv => report.write_csv(out)?,
+            }
+            return Ok(());
+        }
+    };
+    if changed {
+        store.save(&cli.file)?;
+    }
+    Output::Ticket(store.get(id)?).print(cli.json, out)?;
+    Ok(())
+}
+
+fn title_from(title: String) -> Result<TicketTitle, CliError> {
+    TicketTitle::try_from(title).map_err(|e| CliError::Invalid {
+        field: "title",
+        message: e.to_string(),
+    })
+}
+
+fn description_from(description: String) -> Result<TicketDescription, CliError> {
+    TicketDescription::try_from(description).map_err(|e| CliError::Invalid {
+        field: "description",
+        message: e.to_string(),
+    })
+}
+
+/// Parse a `NAME=VALUE` template variable.
+fn parse_variable(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected NAME=VALUE, got `{s}`"))
+}
+
+fn schedule_from(schedule: &str) -> Result<Schedule, CliError> {
+    Schedule::from_str(schedule).map_err(|e| CliError::Invalid {
+        field: "schedule",
+        message: e.to_string(),
+    })
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Output<'a> {
+    Ticket(&'a Ticket),
+    Tickets(Vec<&'a Ticket>),
+    Rules(Vec<RecurringRule>),
+}
+
+impl Output<'_> {
+    fn print(&self, json: bool, out: &mut impl Write) -> io::Result<()> {
+        if json {
+            serde_json::to_writer_pretty(&mut *out, self)?;
+            return writeln!(out);
+        }
+        match self {
+            Output::Ticket(ticket) => {
+                writeln!(out, "Ticket {} [{}]", ticket.id, ticket.status)?;
+                writeln!(out, "{}", ticket.title.as_str())?;
+                writeln!(out)?;
+                writeln!(out, "{}", ticket.description.as_str())?;
+                writeln!(out)?;
+                write!(
+                    out,
+                    "Created {}, updated {}",
+                    ticket.created_at.format(TIME_FORMAT),
+                    ticket.updated_at.format(TIME_FORMAT)
+                )?;
+                match ticket.closed_at {
+                    Some(closed_at) => writeln!(out, ", closed {}", closed_at.format(TIME_FORMAT))?,
+                    None => writeln!(out)?,
+                }
+                if !ticket.comments.is_empty() {
+                    writeln!(out)?;
+                    writeln!(out, "Comments:")?;
+                    for comment in &ticket.comments {
+                        writeln!(out, "- {}", comment.body)?;
+                    }
+                }
+                Ok(())
+            }
+            Output::Tickets(tickets) => {
+                for ticket in tickets {
+                    writeln!(
+                        out,
+                        "{:>4}  {:<11}  {}",
+                        ticket.id,
+                        ticket.status,
+                        ticket.title.as_str()
+                    )?;
+                }
+                Ok(())
+            }
+            Output::Rules(rules) => {
+                for rule in rules {
+                    let next_due = rule
+                        .next_due()
+                        .map_or("never".to_string(), |at| at.format(TIME_FORMAT).to_string());
+                    writeln!(
+                        out,
+                        "{}  [{}]  next due {next_due}  {}",
+                        rule.name,
+                        rule.schedule,
+                        rule.title.as_str()
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn export(tickets: &[&Ticket], format: ExportFormat, out: &mut impl Write) -> io::Result<()> {
+    match format {
+        ExportFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, tickets)?;
+            writeln!(out)
+        }
+        ExportFormat::Csv => {
+            writeln!(out, "id,status,title,description,comments")?;
+            for ticket in tickets {
+                writeln!(
+                    out,
+                    "{},{},{},{},{}",
+                    ticket.id,
+                    ticket.status,
+                    csv_field(ticket.title.as_str()),
+                    csv_field(ticket.description.as_str()),
+                    ticket.comments.len()
+                )?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Quote `value` if needed, as per RFC 4180.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
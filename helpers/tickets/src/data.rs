@@ -0,0 +1,160 @@
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use ticket_fields::{TicketDescription, TicketTitle};
+
+/// Sent as a JSON string, like the ids of the ticket server, so that exports can be read
+/// by the same clients. Ids saved as JSON numbers, as they used to be, are still accepted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TicketId(pub(crate) u64);
+
+impl fmt::Display for TicketId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for TicketId {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+impl Serialize for TicketId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TicketId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(TicketIdVisitor)
+    }
+}
+
+struct TicketIdVisitor;
+
+impl Visitor<'_> for TicketIdVisitor {
+    type Value = TicketId;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a ticket id, as a number or a string")
+    }
+
+    fn visit_u64<E: de::Error>(self, n: u64) -> Result<TicketId, E> {
+        Ok(TicketId(n))
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<TicketId, E> {
+        s.parse().map_err(E::custom)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ticket {
+    pub id: TicketId,
+    pub title: TicketTitle,
+    pub description: TicketDescription,
+    pub status: Status,
+    #[serde(default)]
+    pub comments: Vec<Comment>,
+    /// Tickets saved before timestamps were recorded count as created when they're loaded.
+    #[serde(default = "missing_timestamp")]
+    pub created_at: DateTime<Utc>,
+    /// When the ticket was last changed, comments included.
+    #[serde(default = "missing_timestamp")]
+    pub updated_at: DateTime<Utc>,
+    /// When the ticket was moved to [`Status::Done`], unless it's been reopened since.
+    #[serde(default)]
+    pub closed_at: Option<DateTime<Utc>>,
+    /// Every status the ticket went through, oldest first, starting with its creation.
+    ///
+    /// Empty in files saved before it was recorded, until [`TicketStore::load`]
+    /// fills it in.
+    ///
+    /// [`TicketStore::load`]: crate::store::TicketStore::load
+    #[serde(default)]
+    pub history: Vec<Transition>,
+}
+
+impl Ticket {
+    /// The status the ticket had at `at`, or `None` if it didn't exist yet.
+    pub fn status_at(&self, at: DateTime<Utc>) -> Option<Status> {
+        self.history
+            .iter()
+            .take_while(|transition| transition.at <= at)
+            .last()
+            .map(|transition| transition.status)
+    }
+}
+
+/// The ticket entered `status` at `at`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transition {
+    pub status: Status,
+    pub at: DateTime<Utc>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TicketDraft {
+    pub title: TicketTitle,
+    pub description: TicketDescription,
+}
+
+/// The fields to change: `None` leaves a field untouched.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TicketPatch {
+    pub title: Option<TicketTitle>,
+    pub description: Option<TicketDescription>,
+    pub status: Option<Status>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Comment {
+    pub body: String,
+    #[serde(default = "missing_timestamp")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// Stands for the timestamps missing from files saved before they were recorded,
+/// until [`TicketStore::load_with_clock`](crate::store::TicketStore::load_with_clock)
+/// replaces it with the time of loading.
+pub(crate) fn missing_timestamp() -> DateTime<Utc> {
+    DateTime::<Utc>::MIN_UTC
+}
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+    clap::ValueEnum,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum Status {
+    ToDo,
+    InProgress,
+    Done,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Status::ToDo => "to-do",
+            Status::InProgress => "in-progress",
+            Status::Done => "done",
+        })
+    }
+}
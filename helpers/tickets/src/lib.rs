@@ -0,0 +1,9 @@
+//! A ticket tracker that keeps its tickets in a local file, and the `tickets` CLI to drive it.
+pub mod board;
+pub mod cli;
+pub mod clock;
+pub mod data;
+pub mod recurring;
+pub mod report;
+pub mod store;
+pub mod template;
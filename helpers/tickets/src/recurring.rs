@@ -0,0 +1,58 @@
+//! Tickets that are created again and again on a schedule, e.g. a weekly dependency review.
+//!
+//! Schedules are cron expressions, with a leading seconds field and an optional trailing
+//! year: `sec min hour day-of-month month day-of-week [year]`. For instance,
+//! `0 0 9 * * Mon` is every Monday at 09:00. Times are in UTC.
+//!
+//! Rules don't create tickets by themselves: [`TicketStore::create_due`](crate::store::TicketStore::create_due)
+//! must be called regularly, e.g. by running `tickets recur run` from a cron job.
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+use serde::{Deserialize, Serialize};
+use ticket_fields::{TicketDescription, TicketTitle};
+
+use crate::data::{TicketDraft, TicketId};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecurringRule {
+    /// Unique within a store.
+    pub name: String,
+    pub schedule: Schedule,
+    pub title: TicketTitle,
+    pub description: TicketDescription,
+    /// The occurrences up to this point have been dealt with.
+    pub checked_until: DateTime<Utc>,
+    /// The ticket created for the latest occurrence, if any.
+    pub last_ticket: Option<TicketId>,
+}
+
+impl RecurringRule {
+    /// A rule whose first ticket is due at the first occurrence after `since`.
+    pub fn new(
+        name: impl Into<String>,
+        schedule: Schedule,
+        draft: TicketDraft,
+        since: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            schedule,
+            title: draft.title,
+            description: draft.description,
+            checked_until: since,
+            last_ticket: None,
+        }
+    }
+
+    /// When the next ticket is due, if the schedule has any occurrence left.
+    pub fn next_due(&self) -> Option<DateTime<Utc>> {
+        self.schedule.after(&self.checked_until).next()
+    }
+
+    pub fn draft(&self) -> TicketDraft {
+        TicketDraft {
+            title: self.title.clone(),
+            description: self.description.clone(),
+        }
+    }
+}
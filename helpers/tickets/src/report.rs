@@ -0,0 +1,197 @@
+//! Flow metrics, computed from the status transitions recorded on each ticket.
+//!
+//! - *Lead time*: from the creation of a ticket to its closing.
+//! - *Cycle time*: from the moment work started on a ticket (it first moved to
+//!   [`Status::InProgress`]) to its closing. Tickets that skipped `InProgress` don't have one.
+//! - *Throughput*: how many tickets were closed each week, weeks starting on Monday.
+//! - *Work in progress*: how many tickets were in progress at the end of each day.
+//!
+//! Only tickets that are currently [`Status::Done`] count as closed: a reopened ticket
+//! is closed again, with its original creation date, when it goes back to `Done`.
+//! Days and weeks are in UTC.
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+
+use crate::data::{Status, Ticket};
+
+/// The percentiles reported for lead and cycle times.
+pub const PERCENTILES: [u8; 3] = [50, 85, 95];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    /// The end of the reporting period.
+    pub until: DateTime<Utc>,
+    pub lead_time: Summary,
+    pub cycle_time: Summary,
+    /// Tickets closed per week, keyed by the Monday each week starts on.
+    pub throughput: BTreeMap<NaiveDate, usize>,
+    /// Tickets in progress at the end of each day.
+    pub work_in_progress: BTreeMap<NaiveDate, usize>,
+}
+
+/// Percentiles of a set of durations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Summary {
+    pub count: usize,
+    /// One entry per [`PERCENTILES`], `None` if there's nothing to summarize.
+    pub percentiles: [Option<Duration>; PERCENTILES.len()],
+}
+
+impl Summary {
+    fn new(mut durations: Vec<Duration>) -> Self {
+        durations.sort();
+        Self {
+            count: durations.len(),
+            percentiles: PERCENTILES.map(|p| percentile(&durations, p)),
+        }
+    }
+
+    /// The duration for the given percentile, one of [`PERCENTILES`].
+    pub fn get(&self, percentile: u8) -> Option<Duration> {
+        let index = PERCENTILES.iter().position(|p| *p == percentile)?;
+        self.percentiles[index]
+    }
+}
+
+/// The nearest-rank percentile of `sorted`.
+fn percentile(sorted: &[Duration], percentile: u8) -> Option<Duration> {
+    let rank = (sorted.len() * usize::from(percentile)).div_ceil(100);
+    sorted.get(rank.saturating_sub(1)).copied()
+}
+
+impl Report {
+    /// Compute the metrics of `tickets` from their creation up to `until`.
+    pub fn new<'a>(tickets: impl IntoIterator<Item = &'a Ticket>, until: DateTime<Utc>) -> Self {
+        let tickets: Vec<&Ticket> = tickets.into_iter().collect();
+
+        let mut lead_times = Vec::new();
+        let mut cycle_times = Vec::new();
+        for ticket in &tickets {
+            let Some(closed_at) = ticket.closed_at.filter(|_| ticket.status == Status::Done) else {
+                continue;
+            };
+            lead_times.push(closed_at - ticket.created_at);
+            let started_at = ticket
+                .history
+                .iter()
+                .find(|transition| transition.status == Status::InProgress)
+                .map(|transition| transition.at);
+            if let Some(started_at) = started_at.filter(|at| *at <= closed_at) {
+                cycle_times.push(closed_at - started_at);
+            }
+        }
+
+        let mut throughput = BTreeMap::new();
+        let mut work_in_progress = BTreeMap::new();
+        if let Some(first) = tickets.iter().map(|ticket| ticket.created_at).min() {
+            for day in first.date_naive().iter_days() {
+                if day > until.date_naive() {
+                    break;
+                }
+                throughput.entry(week_of(day)).or_insert(0);
+                let end_of_day = day
+                    .succ_opt()
+                    .and_then(|next| next.and_hms_opt(0, 0, 0))
+                    .map(|midnight| midnight.and_utc() - Duration::nanoseconds(1))
+                    .map_or(until, |end| end.min(until));
+                let in_progress = tickets
+                    .iter()
+                    .filter(|ticket| ticket.status_at(end_of_day) == Some(Status::InProgress))
+                    .count();
+                work_in_progress.insert(day, in_progress);
+            }
+            for ticket in &tickets {
+                if let (Status::Done, Some(closed_at)) = (ticket.status, ticket.closed_at) {
+                    if closed_at <= until {
+                        *throughput
+                            .entry(week_of(closed_at.date_naive()))
+                            .or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        Self {
+            until,
+            lead_time: Summary::new(lead_times),
+            cycle_time: Summary::new(cycle_times),
+            throughput,
+            work_in_progress,
+        }
+    }
+
+    /// Write the report as aligned, human-readable tables.
+    pub fn write_table(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(
+            out,
+            "Flow metrics up to {}",
+            self.until.format("%Y-%m-%d %H:%M UTC")
+        )?;
+        writeln!(out)?;
+        write!(out, "{:<10}  {:>5}", "", "Count")?;
+        for p in PERCENTILES {
+            write!(out, "  {:>8}", format!("p{p}"))?;
+        }
+        writeln!(out)?;
+        for (name, summary) in [
+            ("Lead time", &self.lead_time),
+            ("Cycle time", &self.cycle_time),
+        ] {
+            write!(out, "{name:<10}  {:>5}", summary.count)?;
+            for duration in summary.percentiles {
+                let hours = duration.map_or("-".to_string(), |d| format!("{}h", hours(d)));
+                write!(out, "  {hours:>8}")?;
+            }
+            writeln!(out)?;
+        }
+
+        writeln!(out)?;
+        writeln!(out, "{:<10}  {:>6}", "Week of", "Closed")?;
+        for (week, closed) in &self.throughput {
+            writeln!(out, "{week:<10}  {closed:>6}")?;
+        }
+
+        writeln!(out)?;
+        writeln!(out, "{:<10}  {:>11}", "Day", "In progress")?;
+        for (day, in_progress) in &self.work_in_progress {
+            writeln!(out, "{day:<10}  {in_progress:>11}")?;
+        }
+        Ok(())
+    }
+
+    /// Write the report as CSV, one `metric,period,value` row per figure.
+    ///
+    /// Durations are in hours; `period` is empty for figures covering the whole report.
+    pub fn write_csv(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "metric,period,value")?;
+        for (name, summary) in [
+            ("lead_time", &self.lead_time),
+            ("cycle_time", &self.cycle_time),
+        ] {
+            writeln!(out, "{name}_count,,{}", summary.count)?;
+            for (p, duration) in PERCENTILES.iter().zip(summary.percentiles) {
+                let value = duration.map(hours).unwrap_or_default();
+                writeln!(out, "{name}_p{p}_hours,,{value}")?;
+            }
+        }
+        for (week, closed) in &self.throughput {
+            writeln!(out, "throughput,{week},{closed}")?;
+        }
+        for (day, in_progress) in &self.work_in_progress {
+            writeln!(out, "work_in_progress,{day},{in_progress}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The Monday starting the week `day` is in.
+fn week_of(day: NaiveDate) -> NaiveDate {
+    day - Duration::days(day.weekday().num_days_from_monday().into())
+}
+
+/// `duration` in hours, to one decimal.
+fn hours(duration: Duration) -> String {
+    format!("{:.1}", duration.num_seconds() as f64 / 3600.0)
+}
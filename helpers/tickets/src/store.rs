@@ -0,0 +1,276 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+use serde::{Deserialize, Serialize};
+
+use crate::clock::{Clock, SystemClock};
+use crate::data::{
+    missing_timestamp, Comment, Status, Ticket, TicketDraft, TicketId, TicketPatch, Transition,
+};
+use crate::recurring::RecurringRule;
+use crate::report::Report;
+
+#[derive(Debug, thiserror::Error)]
+pub enum StoreError {
+    #[error("There is no ticket with id {0}")]
+    NotFound(TicketId),
+    #[error("Comments cannot be empty")]
+    EmptyComment,
+    #[error("There is already a recurring ticket named {0}")]
+    RuleExists(String),
+    #[error("There is no recurring ticket named {0}")]
+    UnknownRule(String),
+    #[error("Could not access the ticket file: {0}")]
+    Io(#[from] io::Error),
+    #[error("The ticket file is corrupted: {0}")]
+    Corrupted(#[from] serde_json::Error),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TicketStore {
+    tickets: BTreeMap<TicketId, Ticket>,
+    counter: u64,
+    #[serde(default)]
+    recurring: Vec<RecurringRule>,
+    /// Timestamps every change. Not saved: a loaded store uses the system clock.
+    #[serde(skip, default = "system_clock")]
+    clock: Arc<dyn Clock>,
+}
+
+fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+impl Default for TicketStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TicketStore {
+    pub fn new() -> Self {
+        Self {
+            tickets: BTreeMap::new(),
+            counter: 0,
+            recurring: Vec::new(),
+            clock: system_clock(),
+        }
+    }
+
+    /// Use `clock` to timestamp changes from now on.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Load the store saved at `path`, or start an empty one if there's no such file.
+    pub fn load(path: &Path) -> Result<Self, StoreError> {
+        Self::load_with_clock(path, SystemClock)
+    }
+
+    /// Like [`TicketStore::load`], timestamping changes with `clock` from then on.
+    ///
+    /// Tickets and comments saved without timestamps count as created when they're loaded,
+    /// according to `clock`. Tickets saved without a history get one that's as close as
+    /// can be told: created to do, then moved to their current status when they were
+    /// last updated.
+    pub fn load_with_clock(path: &Path, clock: impl Clock + 'static) -> Result<Self, StoreError> {
+        let store = match fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let mut store = store.with_clock(clock);
+        let now = store.clock.now();
+        for ticket in store.tickets.values_mut() {
+            backfill(ticket, now);
+        }
+        Ok(store)
+    }
+
+    /// Save the store to `path`.
+    ///
+    /// The new content is written next to it first, then moved into place:
+    /// a crash halfway through leaves the previous version intact.
+    pub fn save(&self, path: &Path) -> Result<(), StoreError> {
+        let mut staging = path.as_os_str().to_owned();
+        staging.push(".tmp");
+        fs::write(&staging, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(&staging, path)?;
+        Ok(())
+    }
+
+    pub fn add_ticket(&mut self, draft: TicketDraft) -> TicketId {
+        let id = TicketId(self.counter);
+        self.counter += 1;
+        let now = self.clock.now();
+        let ticket = Ticket {
+            id,
+            title: draft.title,
+            description: draft.description,
+            status: Status::ToDo,
+            comments: Vec::new(),
+            created_at: now,
+            updated_at: now,
+            closed_at: None,
+            history: vec![Transition {
+                status: Status::ToDo,
+                at: now,
+            }],
+        };
+        self.tickets.insert(id, ticket);
+        id
+    }
+
+    pub fn get(&self, id: TicketId) -> Result<&Ticket, StoreError> {
+        self.tickets.get(&id).ok_or(StoreError::NotFound(id))
+    }
+
+    /// The tickets with the given `status`, or all of them if it's `None`, in id order.
+    pub fn list(&self, status: Option<Status>) -> impl Iterator<Item = &Ticket> {
+        self.tickets
+            .values()
+            .filter(move |ticket| status.is_none_or(|status| ticket.status == status))
+    }
+
+    pub fn update(&mut self, id: TicketId, patch: TicketPatch) -> Result<&Ticket, StoreError> {
+        let now = self.clock.now();
+        let ticket = self.get_mut(id)?;
+        if let Some(title) = patch.title {
+            ticket.title = title;
+        }
+        if let Some(description) = patch.description {
+            ticket.description = description;
+        }
+        if let Some(status) = patch.status {
+            if status != ticket.status {
+                ticket.closed_at = (status == Status::Done).then_some(now);
+                ticket.history.push(Transition { status, at: now });
+            }
+            ticket.status = status;
+        }
+        ticket.updated_at = now;
+        Ok(ticket)
+    }
+
+    pub fn comment(&mut self, id: TicketId, body: String) -> Result<&Ticket, StoreError> {
+        if body.trim().is_empty() {
+            return Err(StoreError::EmptyComment);
+        }
+        let now = self.clock.now();
+        let ticket = self.get_mut(id)?;
+        ticket.comments.push(Comment {
+            body,
+            created_at: now,
+        });
+        ticket.updated_at = now;
+        Ok(ticket)
+    }
+
+    /// Add a rule whose first ticket is due at the next occurrence of `schedule`.
+    pub fn add_recurring(
+        &mut self,
+        name: String,
+        schedule: Schedule,
+        draft: TicketDraft,
+    ) -> Result<&RecurringRule, StoreError> {
+        if self.recurring.iter().any(|rule| rule.name == name) {
+            return Err(StoreError::RuleExists(name));
+        }
+        let rule = RecurringRule::new(name, schedule, draft, self.clock.now());
+        self.recurring.push(rule);
+        Ok(&self.recurring[self.recurring.len() - 1])
+    }
+
+    pub fn remove_recurring(&mut self, name: &str) -> Result<RecurringRule, StoreError> {
+        let index = self
+            .recurring
+            .iter()
+            .position(|rule| rule.name == name)
+            .ok_or_else(|| StoreError::UnknownRule(name.to_string()))?;
+        Ok(self.recurring.remove(index))
+    }
+
+    /// The recurring ticket rules, in the order they were added.
+    pub fn recurring(&self) -> impl Iterator<Item = &RecurringRule> {
+        self.recurring.iter()
+    }
+
+    /// Create a ticket for each rule that has come due since it was last checked.
+    ///
+    /// A rule creates at most one ticket per call, however many occurrences were missed,
+    /// and none while the ticket it created last is still open: that occurrence is skipped.
+    pub fn create_due(&mut self) -> Vec<TicketId> {
+        let now = self.clock.now();
+        let mut due = Vec::new();
+        for (index, rule) in self.recurring.iter_mut().enumerate() {
+            if rule.next_due().is_none_or(|at| at > now) {
+                continue;
+            }
+            rule.checked_until = now;
+            let still_open = rule
+                .last_ticket
+                .and_then(|id| self.tickets.get(&id))
+                .is_some_and(|ticket| ticket.status != Status::Done);
+            if !still_open {
+                due.push(index);
+            }
+        }
+        due.into_iter()
+            .map(|index| {
+                let id = self.add_ticket(self.recurring[index].draft());
+                self.recurring[index].last_ticket = Some(id);
+                id
+            })
+            .collect()
+    }
+
+    /// Flow metrics for all the tickets, up to now.
+    pub fn report(&self) -> Report {
+        Report::new(self.tickets.values(), self.clock.now())
+    }
+
+    fn get_mut(&mut self, id: TicketId) -> Result<&mut Ticket, StoreError> {
+        self.tickets.get_mut(&id).ok_or(StoreError::NotFound(id))
+    }
+}
+
+/// Fill in what files saved by earlier versions lack, `now` being the time of loading.
+fn backfill(ticket: &mut Ticket, now: DateTime<Utc>) {
+    let missing = missing_timestamp();
+    for timestamp in [&mut ticket.created_at, &mut ticket.updated_at]
+        .into_iter()
+        .chain(
+            ticket
+                .comments
+                .iter_mut()
+                .map(|comment| &mut comment.created_at),
+        )
+    {
+        if *timestamp == missing {
+            *timestamp = now;
+        }
+    }
+    if ticket.history.is_empty() {
+        ticket.history = backfilled_history(ticket);
+    }
+}
+
+fn backfilled_history(ticket: &Ticket) -> Vec<Transition> {
+    let mut history = vec![Transition {
+        status: Status::ToDo,
+        at: ticket.created_at,
+    }];
+    if ticket.status != Status::ToDo {
+        history.push(Transition {
+            status: ticket.status,
+            at: ticket.closed_at.unwrap_or(ticket.updated_at),
+        });
+    }
+    history
+}
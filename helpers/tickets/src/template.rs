@@ -0,0 +1,130 @@
+//! Ticket templates: drafts with `{{variable}}` placeholders, for the kinds of ticket
+//! that are filed over and over again.
+//!
+//! Templates are kept in a TOML file, one table per template:
+//!
+//! ```toml
+//! [bug]
+//! title = "{{component}} is broken"
+//! description = """
+//! Component: {{component}}
+//! Version: {{version}}
+//!
+//! Steps to reproduce:
+//! """
+//! ```
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use ticket_fields::{TicketDescription, TicketTitle};
+
+use crate::data::TicketDraft;
+
+/// A [`TicketDraft`] whose title and description may contain `{{variable}}` placeholders.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Template {
+    pub title: String,
+    pub description: String,
+}
+
+/// Templates, by name.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Templates(BTreeMap<String, Template>);
+
+#[derive(Debug, thiserror::Error)]
+pub enum TemplateError {
+    #[error("There is no template named {0}")]
+    UnknownTemplate(String),
+    #[error("Missing values for: {}", .0.join(", "))]
+    MissingVariables(Vec<String>),
+    #[error("A placeholder isn't closed: {0}")]
+    UnclosedPlaceholder(String),
+    /// The rendered text isn't a valid title or description, e.g. it's too long.
+    #[error("Invalid rendered {field}: {message}")]
+    Invalid {
+        field: &'static str,
+        message: String,
+    },
+    #[error("Could not read the template file: {0}")]
+    Io(#[from] io::Error),
+    #[error("The template file is corrupted: {0}")]
+    Corrupted(#[from] toml::de::Error),
+}
+
+impl Templates {
+    /// Load the templates from the TOML file at `path`.
+    pub fn load(path: &Path) -> Result<Self, TemplateError> {
+        Self::from_toml(&fs::read_to_string(path)?)
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self, TemplateError> {
+        Ok(toml::from_str(toml)?)
+    }
+
+    pub fn get(&self, name: &str) -> Result<&Template, TemplateError> {
+        self.0
+            .get(name)
+            .ok_or_else(|| TemplateError::UnknownTemplate(name.to_string()))
+    }
+}
+
+impl Template {
+    /// Fill in the placeholders with `variables`, and validate the result.
+    ///
+    /// Every missing variable is reported at once, title and description together.
+    /// Variables that no placeholder uses are ignored.
+    pub fn render(
+        &self,
+        variables: &BTreeMap<String, String>,
+    ) -> Result<TicketDraft, TemplateError> {
+        let mut missing = Vec::new();
+        let title = substitute(&self.title, variables, &mut missing)?;
+        let description = substitute(&self.description, variables, &mut missing)?;
+        if !missing.is_empty() {
+            missing.sort();
+            missing.dedup();
+            return Err(TemplateError::MissingVariables(missing));
+        }
+        Ok(TicketDraft {
+            title: TicketTitle::try_from(title).map_err(|e| TemplateError::Invalid {
+                field: "title",
+                message: e.to_string(),
+            })?,
+            description: TicketDescription::try_from(description).map_err(|e| {
+                TemplateError::Invalid {
+                    field: "description",
+                    message: e.to_string(),
+                }
+            })?,
+        })
+    }
+}
+
+/// Replace the placeholders in `text`, adding the variables without a value to `missing`.
+fn substitute(
+    text: &str,
+    variables: &BTreeMap<String, String>,
+    missing: &mut Vec<String>,
+) -> Result<String, TemplateError> {
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let placeholder = &rest[start + 2..];
+        let end = placeholder
+            .find("}}")
+            .ok_or_else(|| TemplateError::UnclosedPlaceholder(rest[start..].to_string()))?;
+        let name = placeholder[..end].trim();
+        match variables.get(name) {
+            Some(value) => rendered.push_str(value),
+            None => missing.push(name.to_string()),
+        }
+        rest = &placeholder[end + 2..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
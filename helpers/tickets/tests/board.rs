@@ -0,0 +1,124 @@
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::Terminal;
+use ticket_fields::test_helpers::ticket_description;
+use ticket_fields::TicketTitle;
+use tickets::board::{Board, Control};
+use tickets::data::{Status, TicketDraft};
+use tickets::store::TicketStore;
+
+fn board(titles: &[&str]) -> Board {
+    let mut store = TicketStore::new();
+    for title in titles {
+        store.add_ticket(TicketDraft {
+            title: TicketTitle::try_from(*title).unwrap(),
+            description: ticket_description(),
+        });
+    }
+    Board::new(store)
+}
+
+fn press(board: &mut Board, keys: &str) -> Control {
+    let mut control = Control::Continue;
+    for c in keys.chars() {
+        control = board.handle_key(KeyEvent::from(KeyCode::Char(c)));
+    }
+    control
+}
+
+fn press_key(board: &mut Board, code: KeyCode) -> Control {
+    board.handle_key(KeyEvent::from(code))
+}
+
+/// The text on screen, one line per row.
+fn screen(board: &Board) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(90, 20)).unwrap();
+    terminal.draw(|frame| board.render(frame)).unwrap();
+    let buffer = terminal.backend().buffer();
+    buffer
+        .content()
+        .chunks(buffer.area.width as usize)
+        .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn tickets_are_shown_in_their_column() {
+    let board = board(&["First", "Second"]);
+    let screen = screen(&board);
+    assert!(screen.contains("To do (2)"));
+    assert!(screen.contains("In progress (0)"));
+    assert!(screen.contains("> 0 First"));
+    assert!(screen.contains("1 Second"));
+}
+
+#[test]
+fn cards_can_be_moved_between_columns() {
+    let mut board = board(&["First", "Second"]);
+
+    assert_eq!(press(&mut board, "j>"), Control::Changed);
+    let moved = board.selected().unwrap();
+    assert_eq!(moved.title.as_str(), "Second");
+    assert_eq!(moved.status, Status::InProgress);
+
+    press(&mut board, ">");
+    assert_eq!(board.selected().unwrap().status, Status::Done);
+    // There's no column past `Done`.
+    assert_eq!(press(&mut board, ">"), Control::Continue);
+
+    let screen = screen(&board);
+    assert!(screen.contains("To do (1)"));
+    assert!(screen.contains("Done (1)"));
+}
+
+#[test]
+fn editing_validates_as_you_type() {
+    let mut board = board(&["Typo"]);
+    press(&mut board, "e");
+    for _ in "Typo".chars() {
+        press_key(&mut board, KeyCode::Backspace);
+    }
+    assert!(screen(&board).contains("The title cannot be empty"));
+
+    // Invalid values can't be saved.
+    assert_eq!(press_key(&mut board, KeyCode::Enter), Control::Continue);
+    assert!(screen(&board).contains("Edit ticket 0"));
+
+    press(&mut board, "Fixed");
+    assert!(!screen(&board).contains("The title cannot be empty"));
+    assert_eq!(press_key(&mut board, KeyCode::Enter), Control::Changed);
+    assert_eq!(board.selected().unwrap().title.as_str(), "Fixed");
+}
+
+#[test]
+fn cancelled_edits_are_discarded() {
+    let mut board = board(&["Original"]);
+    press(&mut board, "e");
+    press_key(&mut board, KeyCode::Tab);
+    press(&mut board, " and more");
+    assert_eq!(press_key(&mut board, KeyCode::Esc), Control::Continue);
+    assert_eq!(board.selected().unwrap().description, ticket_description());
+}
+
+#[test]
+fn search_filters_the_cards() {
+    let mut board = board(&["Fix login", "Write docs", "Fix logout"]);
+    press(&mut board, "/logout");
+    press_key(&mut board, KeyCode::Enter);
+
+    let shown = screen(&board);
+    assert!(shown.contains("To do (1)"));
+    assert!(shown.contains("Fix logout"));
+    assert!(!shown.contains("Write docs"));
+    assert_eq!(board.selected().unwrap().title.as_str(), "Fix logout");
+
+    press_key(&mut board, KeyCode::Esc);
+    assert!(screen(&board).contains("To do (3)"));
+}
+
+#[test]
+fn q_quits() {
+    let mut board = board(&[]);
+    assert_eq!(press(&mut board, "q"), Control::Quit);
+}
@@ -0,0 +1,192 @@
+use std::path::Path;
+use std::process::{Command, Output};
+
+use serde_json::Value;
+use tickets::cli::{EXIT_INVALID, EXIT_NOT_FOUND, EXIT_USAGE};
+
+fn tickets(file: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_tickets"))
+        .arg("--file")
+        .arg(file)
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn json(output: &Output) -> Value {
+    assert!(output.status.success(), "{output:?}");
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn ticket_lifecycle() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("tickets.json");
+
+    let created = json(&tickets(
+        &file,
+        &[
+            "--json",
+            "new",
+            "--title",
+            "Fix it",
+            "--description",
+            "It's broken",
+        ],
+    ));
+    assert_eq!(created["status"], "to-do");
+    let id = created["id"].as_str().unwrap().to_string();
+
+    tickets(&file, &["move", &id, "in-progress"]);
+    tickets(&file, &["edit", &id, "--title", "Fix it properly"]);
+    tickets(&file, &["comment", &id, "On it"]);
+
+    let shown = json(&tickets(&file, &["show", &id, "--json"]));
+    assert_eq!(shown["title"], "Fix it properly");
+    assert_eq!(shown["description"], "It's broken");
+    assert_eq!(shown["status"], "in-progress");
+    assert_eq!(shown["comments"][0]["body"], "On it");
+
+    let text = tickets(&file, &["show", &id]);
+    let text = String::from_utf8(text.stdout).unwrap();
+    assert!(text.starts_with(&format!("Ticket {id} [in-progress]\nFix it properly\n")));
+
+    let listed = json(&tickets(
+        &file,
+        &["--json", "list", "--status", "in-progress"],
+    ));
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+    let listed = json(&tickets(&file, &["--json", "list", "--status", "done"]));
+    assert_eq!(listed, Value::Array(vec![]));
+}
+
+#[test]
+fn export_as_csv() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("tickets.json");
+    tickets(
+        &file,
+        &[
+            "new",
+            "--title",
+            "Quotes",
+            "--description",
+            "Say \"hi\", then leave",
+        ],
+    );
+
+    let exported = tickets(&file, &["export", "--format", "csv"]);
+    assert_eq!(
+        String::from_utf8(exported.stdout).unwrap(),
+        "id,status,title,description,comments\n0,to-do,Quotes,\"Say \"\"hi\"\", then leave\",0\n"
+    );
+}
+
+#[test]
+fn exit_codes_tell_failures_apart() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("tickets.json");
+
+    let invalid = tickets(
+        &file,
+        &["new", "--title", "", "--description", "Empty title"],
+    );
+    assert_eq!(invalid.status.code(), Some(EXIT_INVALID.into()));
+    assert!(String::from_utf8(invalid.stderr).unwrap().contains("title"));
+
+    let missing = tickets(&file, &["show", "42"]);
+    assert_eq!(missing.status.code(), Some(EXIT_NOT_FOUND.into()));
+
+    let usage = tickets(&file, &["move", "42", "sideways"]);
+    assert_eq!(usage.status.code(), Some(EXIT_USAGE.into()));
+
+    // Nothing was saved by the failed commands.
+    assert!(!file.exists());
+}
+
+#[test]
+fn tickets_can_be_created_from_templates() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("tickets.json");
+    let templates = dir.path().join("templates.toml");
+    std::fs::write(
+        &templates,
+        "[bug]\ntitle = \"{{component}} is broken\"\ndescription = \"Seen in {{version}}\"\n",
+    )
+    .unwrap();
+    let templates = templates.to_str().unwrap();
+
+    let created = json(&tickets(
+        &file,
+        &[
+            "--json",
+            "--templates",
+            templates,
+            "new",
+            "--template",
+            "bug",
+            "--var",
+            "component=parser",
+            "--var",
+            "version=1.2",
+        ],
+    ));
+    assert_eq!(created["title"], "parser is broken");
+    assert_eq!(created["description"], "Seen in 1.2");
+
+    let missing = tickets(
+        &file,
+        &["--templates", templates, "new", "--template", "bug"],
+    );
+    assert_eq!(missing.status.code(), Some(EXIT_INVALID.into()));
+    assert!(String::from_utf8(missing.stderr)
+        .unwrap()
+        .contains("Missing values for: component, version"));
+}
+
+#[test]
+fn recurring_tickets_are_saved() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("tickets.json");
+
+    let added = json(&tickets(
+        &file,
+        &[
+            "--json",
+            "recur",
+            "add",
+            "certificates",
+            "--schedule",
+            "0 0 0 1 * *",
+            "--title",
+            "Rotate certificates",
+            "--description",
+            "Before they expire",
+        ],
+    ));
+    assert_eq!(added[0]["name"], "certificates");
+
+    let listed = json(&tickets(&file, &["--json", "recur", "list"]));
+    assert_eq!(listed[0]["schedule"], "0 0 0 1 * *");
+    // The first occurrence is in the future.
+    let created = json(&tickets(&file, &["--json", "recur", "run"]));
+    assert_eq!(created, Value::Array(vec![]));
+
+    let invalid = tickets(
+        &file,
+        &[
+            "recur",
+            "add",
+            "never",
+            "--schedule",
+            "whenever",
+            "--title",
+            "Title",
+            "--description",
+            "Description",
+        ],
+    );
+    assert_eq!(invalid.status.code(), Some(EXIT_INVALID.into()));
+    let missing = tickets(&file, &["recur", "remove", "never"]);
+    assert_eq!(missing.status.code(), Some(EXIT_NOT_FOUND.into()));
+}
@@ -0,0 +1,95 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, Utc};
+use cron::Schedule;
+use ticket_fields::test_helpers::{ticket_description, ticket_title};
+use tickets::clock::ManualClock;
+use tickets::data::{Status, TicketDraft, TicketId, TicketPatch};
+use tickets::store::{StoreError, TicketStore};
+
+fn draft() -> TicketDraft {
+    TicketDraft {
+        title: ticket_title(),
+        description: ticket_description(),
+    }
+}
+
+fn start() -> DateTime<Utc> {
+    // A Sunday.
+    "2024-05-05T12:00:00Z".parse().unwrap()
+}
+
+fn every_monday() -> Schedule {
+    Schedule::from_str("0 0 9 * * Mon").unwrap()
+}
+
+fn close(store: &mut TicketStore, id: TicketId) {
+    let patch = TicketPatch {
+        status: Some(Status::Done),
+        ..TicketPatch::default()
+    };
+    store.update(id, patch).unwrap();
+}
+
+#[test]
+fn tickets_are_created_when_due() {
+    let clock = ManualClock::new(start());
+    let mut store = TicketStore::new().with_clock(clock.clone());
+    store
+        .add_recurring("review".into(), every_monday(), draft())
+        .unwrap();
+    let next_due = store.recurring().next().unwrap().next_due();
+    assert_eq!(next_due, Some("2024-05-06T09:00:00Z".parse().unwrap()));
+    assert!(store.create_due().is_empty());
+
+    clock.set("2024-05-06T09:00:00Z".parse().unwrap());
+    let created = store.create_due();
+    assert_eq!(created.len(), 1);
+    assert_eq!(store.get(created[0]).unwrap().title, ticket_title());
+    // Each occurrence is only handled once.
+    assert!(store.create_due().is_empty());
+
+    close(&mut store, created[0]);
+    clock.advance(Duration::weeks(1));
+    assert_eq!(store.create_due().len(), 1);
+}
+
+#[test]
+fn open_tickets_are_not_duplicated() {
+    let clock = ManualClock::new(start());
+    let mut store = TicketStore::new().with_clock(clock.clone());
+    store
+        .add_recurring("review".into(), every_monday(), draft())
+        .unwrap();
+
+    clock.advance(Duration::days(1));
+    let first = store.create_due();
+    assert_eq!(first.len(), 1);
+
+    // The previous ticket is still open: this week's occurrence is skipped.
+    clock.advance(Duration::weeks(1));
+    assert!(store.create_due().is_empty());
+
+    // Missed occurrences don't pile up: one ticket is created for all of them.
+    close(&mut store, first[0]);
+    clock.advance(Duration::weeks(3));
+    assert_eq!(store.create_due().len(), 1);
+    assert_eq!(store.list(Some(Status::ToDo)).count(), 1);
+}
+
+#[test]
+fn rules_are_named_uniquely() {
+    let mut store = TicketStore::new();
+    store
+        .add_recurring("review".into(), every_monday(), draft())
+        .unwrap();
+    assert!(matches!(
+        store.add_recurring("review".into(), every_monday(), draft()),
+        Err(StoreError::RuleExists(_))
+    ));
+    assert_eq!(store.remove_recurring("review").unwrap().name, "review");
+    assert!(matches!(
+        store.remove_recurring("review"),
+        Err(StoreError::UnknownRule(_))
+    ));
+}
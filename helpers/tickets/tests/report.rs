@@ -0,0 +1,112 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use ticket_fields::test_helpers::{ticket_description, ticket_title};
+use tickets::clock::ManualClock;
+use tickets::data::{Status, TicketDraft, TicketId, TicketPatch};
+use tickets::store::TicketStore;
+
+fn start() -> DateTime<Utc> {
+    // A Monday.
+    "2024-05-06T09:00:00Z".parse().unwrap()
+}
+
+fn date(s: &str) -> NaiveDate {
+    s.parse().unwrap()
+}
+
+fn add(store: &mut TicketStore) -> TicketId {
+    store.add_ticket(TicketDraft {
+        title: ticket_title(),
+        description: ticket_description(),
+    })
+}
+
+fn move_to(store: &mut TicketStore, id: TicketId, status: Status) {
+    let patch = TicketPatch {
+        status: Some(status),
+        ..TicketPatch::default()
+    };
+    store.update(id, patch).unwrap();
+}
+
+/// Three tickets over two weeks: two closed, one of them without going through
+/// `InProgress`, and one still in progress.
+fn store() -> TicketStore {
+    let clock = ManualClock::new(start());
+    let mut store = TicketStore::new().with_clock(clock.clone());
+    let a = add(&mut store);
+    let b = add(&mut store);
+    let c = add(&mut store);
+
+    clock.advance(Duration::hours(2));
+    move_to(&mut store, a, Status::InProgress);
+    clock.advance(Duration::hours(4));
+    move_to(&mut store, a, Status::Done);
+
+    clock.advance(Duration::days(1));
+    move_to(&mut store, c, Status::InProgress);
+
+    clock.set(start() + Duration::days(7));
+    move_to(&mut store, b, Status::Done);
+    store
+}
+
+#[test]
+fn transitions_are_recorded() {
+    let store = store();
+    let history: Vec<_> = store
+        .get(store.list(None).next().unwrap().id)
+        .unwrap()
+        .history
+        .iter()
+        .map(|transition| (transition.status, transition.at - start()))
+        .collect();
+    assert_eq!(
+        history,
+        [
+            (Status::ToDo, Duration::zero()),
+            (Status::InProgress, Duration::hours(2)),
+            (Status::Done, Duration::hours(6)),
+        ]
+    );
+}
+
+#[test]
+fn flow_metrics() {
+    let report = store().report();
+
+    assert_eq!(report.lead_time.count, 2);
+    assert_eq!(report.lead_time.get(50), Some(Duration::hours(6)));
+    assert_eq!(report.lead_time.get(95), Some(Duration::days(7)));
+    // Only the ticket that went through `InProgress` has a cycle time.
+    assert_eq!(report.cycle_time.count, 1);
+    assert_eq!(report.cycle_time.get(85), Some(Duration::hours(4)));
+
+    assert_eq!(
+        report.throughput.into_iter().collect::<Vec<_>>(),
+        [(date("2024-05-06"), 1), (date("2024-05-13"), 1)]
+    );
+    assert_eq!(report.work_in_progress.len(), 8);
+    assert_eq!(report.work_in_progress[&date("2024-05-06")], 0);
+    assert_eq!(report.work_in_progress[&date("2024-05-07")], 1);
+    assert_eq!(report.work_in_progress[&date("2024-05-13")], 1);
+}
+
+#[test]
+fn csv_output() {
+    let mut csv = Vec::new();
+    store().report().write_csv(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    assert!(csv.starts_with(
+        "metric,period,value\n\
+         lead_time_count,,2\n\
+         lead_time_p50_hours,,6.0\n\
+         lead_time_p85_hours,,168.0\n"
+    ));
+    assert!(csv.contains("\nthroughput,2024-05-13,1\n"));
+    assert!(csv.ends_with("\nwork_in_progress,2024-05-13,1\n"));
+
+    let mut table = Vec::new();
+    TicketStore::new().report().write_table(&mut table).unwrap();
+    let table = String::from_utf8(table).unwrap();
+    assert!(table.contains("Lead time       0         -         -         -\n"));
+}
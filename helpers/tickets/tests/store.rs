@@ -0,0 +1,157 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Duration, Utc};
+use ticket_fields::test_helpers::{ticket_description, ticket_title};
+use tickets::clock::ManualClock;
+use tickets::data::{Status, TicketDraft, TicketId, TicketPatch};
+use tickets::store::TicketStore;
+
+fn draft() -> TicketDraft {
+    TicketDraft {
+        title: ticket_title(),
+        description: ticket_description(),
+    }
+}
+
+fn start() -> DateTime<Utc> {
+    "2024-05-06T09:00:00Z".parse().unwrap()
+}
+
+fn moved_to(status: Status) -> TicketPatch {
+    TicketPatch {
+        status: Some(status),
+        ..TicketPatch::default()
+    }
+}
+
+#[test]
+fn changes_are_timestamped() {
+    let clock = ManualClock::new(start());
+    let mut store = TicketStore::new().with_clock(clock.clone());
+    let id = store.add_ticket(draft());
+    let ticket = store.get(id).unwrap();
+    assert_eq!(ticket.created_at, start());
+    assert_eq!(ticket.updated_at, start());
+    assert_eq!(ticket.closed_at, None);
+
+    clock.advance(Duration::hours(1));
+    store.update(id, moved_to(Status::InProgress)).unwrap();
+    clock.advance(Duration::hours(1));
+    store.comment(id, "Halfway there".into()).unwrap();
+    let ticket = store.get(id).unwrap();
+    assert_eq!(ticket.created_at, start());
+    assert_eq!(ticket.updated_at, start() + Duration::hours(2));
+    assert_eq!(ticket.comments[0].created_at, start() + Duration::hours(2));
+}
+
+#[test]
+fn closing_and_reopening() {
+    let clock = ManualClock::new(start());
+    let mut store = TicketStore::new().with_clock(clock.clone());
+    let id = store.add_ticket(draft());
+
+    clock.advance(Duration::days(1));
+    store.update(id, moved_to(Status::Done)).unwrap();
+    let closed_at = start() + Duration::days(1);
+    assert_eq!(store.get(id).unwrap().closed_at, Some(closed_at));
+
+    // Edits don't move the closing time.
+    clock.advance(Duration::days(1));
+    store.update(id, moved_to(Status::Done)).unwrap();
+    assert_eq!(store.get(id).unwrap().closed_at, Some(closed_at));
+
+    store.update(id, moved_to(Status::ToDo)).unwrap();
+    assert_eq!(store.get(id).unwrap().closed_at, None);
+}
+
+#[test]
+fn timestamps_are_saved() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("tickets.json");
+    let mut store = TicketStore::new().with_clock(ManualClock::new(start()));
+    let id = store.add_ticket(draft());
+    store.save(&file).unwrap();
+
+    let loaded = TicketStore::load(&file).unwrap();
+    assert_eq!(loaded.get(id).unwrap(), store.get(id).unwrap());
+}
+
+#[test]
+fn ids_are_saved_as_strings() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("tickets.json");
+    let mut store = TicketStore::new();
+    let id = store.add_ticket(draft());
+    store.save(&file).unwrap();
+
+    let saved: serde_json::Value = serde_json::from_slice(&std::fs::read(&file).unwrap()).unwrap();
+    assert_eq!(saved["tickets"]["0"]["id"], "0");
+    assert_eq!(TicketStore::load(&file).unwrap().get(id).unwrap().id, id);
+}
+
+#[test]
+fn files_without_history_still_load() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("tickets.json");
+    std::fs::write(
+        &file,
+        r#"{
+            "tickets": {
+                "0": {
+                    "id": 0,
+                    "title": "Old",
+                    "description": "Saved before the history",
+                    "status": "in-progress",
+                    "comments": [],
+                    "created_at": "2024-05-06T09:00:00Z",
+                    "updated_at": "2024-05-07T09:00:00Z",
+                    "closed_at": null
+                }
+            },
+            "counter": 1
+        }"#,
+    )
+    .unwrap();
+
+    let store = TicketStore::load(&file).unwrap();
+    let ticket = store.list(None).next().unwrap();
+    assert_eq!(ticket.status_at(start()), Some(Status::ToDo));
+    assert_eq!(
+        ticket.status_at(start() + Duration::days(1)),
+        Some(Status::InProgress)
+    );
+}
+
+#[test]
+fn files_without_timestamps_still_load() {
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("tickets.json");
+    std::fs::write(
+        &file,
+        r#"{
+            "tickets": {
+                "0": {
+                    "id": 0,
+                    "title": "Old",
+                    "description": "Saved before timestamps",
+                    "status": "done",
+                    "comments": [{ "body": "Done already" }]
+                }
+            },
+            "counter": 1
+        }"#,
+    )
+    .unwrap();
+
+    let clock = ManualClock::new(start());
+    let mut store = TicketStore::load_with_clock(&file, clock.clone()).unwrap();
+    let ticket = store.get(TicketId::from_str("0").unwrap()).unwrap();
+    assert_eq!(ticket.created_at, start());
+    assert_eq!(ticket.updated_at, start());
+    assert_eq!(ticket.closed_at, None);
+    assert_eq!(ticket.comments[0].created_at, start());
+    assert_eq!(ticket.status_at(start()), Some(Status::Done));
+
+    // Ids carry on from the saved counter.
+    assert_eq!(store.add_ticket(draft()).to_string(), "1");
+}
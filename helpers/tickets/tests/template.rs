@@ -0,0 +1,87 @@
+use std::collections::BTreeMap;
+
+use tickets::template::{TemplateError, Templates};
+
+const TEMPLATES: &str = r#"
+[bug]
+title = "{{component}} is broken"
+description = """
+Component: {{ component }}
+Version: {{version}}
+"""
+
+[chore]
+title = "Chore"
+description = "{{details}}"
+
+[broken]
+title = "{{component"
+description = "Unclosed"
+"#;
+
+fn variables(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn templates_are_rendered_into_drafts() {
+    let templates = Templates::from_toml(TEMPLATES).unwrap();
+    let draft = templates
+        .get("bug")
+        .unwrap()
+        .render(&variables(&[
+            ("component", "parser"),
+            ("version", "1.2"),
+            ("unused", "ignored"),
+        ]))
+        .unwrap();
+    assert_eq!(draft.title.as_str(), "parser is broken");
+    assert_eq!(
+        draft.description.as_str(),
+        "Component: parser\nVersion: 1.2\n"
+    );
+}
+
+#[test]
+fn rendering_errors_are_reported() {
+    let templates = Templates::from_toml(TEMPLATES).unwrap();
+    let render = |name: &str, pairs: &[(&str, &str)]| {
+        templates
+            .get(name)
+            .and_then(|template| template.render(&variables(pairs)))
+            .unwrap_err()
+    };
+
+    let error = render("bug", &[]);
+    assert!(
+        matches!(&error, TemplateError::MissingVariables(missing) if missing == &["component", "version"])
+    );
+    assert_eq!(error.to_string(), "Missing values for: component, version");
+
+    let error = render("chore", &[("details", &"x".repeat(501))]);
+    assert_eq!(
+        error.to_string(),
+        "Invalid rendered description: The description cannot be longer than 500 bytes"
+    );
+
+    assert!(matches!(
+        render("broken", &[("component", "parser")]),
+        TemplateError::UnclosedPlaceholder(_)
+    ));
+    assert!(matches!(
+        render("feature", &[]),
+        TemplateError::UnknownTemplate(_)
+    ));
+}
+
+#[test]
+fn invalid_template_files_are_rejected() {
+    let missing_description = "[bug]\ntitle = \"Bug\"\n";
+    assert!(matches!(
+        Templates::from_toml(missing_description),
+        Err(TemplateError::Corrupted(_))
+    ));
+}